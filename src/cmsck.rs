@@ -1,9 +1,13 @@
 use std::error::Error;
 use std::sync::Arc;
-use std::collections::HashSet;
-use reqwest::{Client, Response};
+use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use regex::Regex;
+use reqwest::{Client, Response, Url};
 use serde::Deserialize;
-use ring::digest::{Context, Digest, SHA256};
+use base64::engine::Engine as _;
+use base64::engine::general_purpose::STANDARD;
 use tokio::sync::Semaphore;
 use scraper::{Html, Selector};
 use tokio::sync::Mutex;
@@ -17,59 +21,337 @@ use sha2::{Digest as Digest_sha2, Sha256 as Sha256_sha2};
 use crate::infoscan::{OtherSets};
 use crate::tofile::editor_urls_save_to_file;
 use crate::pocscan::pocsmain;
+use crate::resource::{self, Resource};
+use crate::fingerimport;
+use crate::validate::{self, Format, Issue, Problem};
+fn default_method() -> String { "keyword".to_string() }
+fn default_location() -> String { "body".to_string() }
+fn default_logic() -> String { "and".to_string() }
+
 #[allow(dead_code)]
-#[derive(Debug, Clone,Deserialize)]
-struct FingerPrint {
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct FingerPrint {
+    #[serde(default)]
     cms: String,
+    #[serde(default)]
     rule: Vec<String>,
+    #[serde(default = "default_location")]
     location: String,
+    #[serde(default = "default_logic")]
     logic: String,
+    #[serde(default = "default_method")]
     method:String,
+    // method 为 combined 时的子条件
+    #[serde(default)]
+    conditions: Vec<FingerPrint>,
+    // 命中后一并输出的关联产品（如 Wappalyzer 的 implies）
+    #[serde(default)]
+    pub(crate) implies: Vec<String>,
+    // 版本提取规则
+    #[serde(default, rename = "version")]
+    version_extractors: Vec<VersionExtractor>,
+    // 产品元数据
+    #[serde(default)]
+    vendor: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    cpe: Option<String>,
+    // method 为 regex 时预编译的规则
+    #[serde(skip)]
+    regexes: Vec<Regex>,
 }
 impl FingerPrint {
+    pub(crate) fn new(cms: &str, method: &str, location: &str, logic: &str, rule: Vec<String>) -> Self {
+        FingerPrint {
+            cms: cms.to_string(),
+            method: method.to_string(),
+            location: location.to_string(),
+            logic: logic.to_string(),
+            rule,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn combined(cms: &str, logic: &str, conditions: Vec<FingerPrint>) -> Self {
+        FingerPrint {
+            conditions,
+            ..FingerPrint::new(cms, "combined", "body", logic, vec![])
+        }
+    }
+
+    // 判断两条指纹规则是否完全相同（忽略产品名）
+    fn same_rule(&self, other: &FingerPrint) -> bool {
+        self.method == other.method
+            && self.location == other.location
+            && self.logic == other.logic
+            && self.rule == other.rule
+            && self.conditions.len() == other.conditions.len()
+            && self.conditions.iter().zip(&other.conditions).all(|(a, b)| a.same_rule(b))
+    }
+
+    // 校验单条指纹规则（递归检查 combined 子条件），error 级问题会导致加载时跳过
+    fn lint(&self, problems: &mut Vec<Problem>) {
+        let method = Method::from_str(&self.method);
+        if method == Method::Unknown {
+            problems.push(Problem::error(format!("unknown method '{}'", self.method)).at(&self.method));
+        }
+        if !["title", "header", "body", "cookie"].contains(&self.location.as_str()) {
+            problems.push(Problem::error(format!("unknown location '{}'", self.location)).at(&self.location));
+        }
+        if self.logic != "and" && self.logic != "or" {
+            problems.push(Problem::error(format!("invalid logic '{}', expected and/or", self.logic)).at(&self.logic));
+        }
+        match method {
+            Method::Combined if self.conditions.is_empty() => problems.push(Problem::error("combined rule without conditions")),
+            Method::Combined => {
+                for condition in &self.conditions {
+                    condition.lint(problems);
+                }
+            }
+            Method::Unknown => {}
+            _ if self.rule.is_empty() || self.rule.iter().any(|r| r.is_empty()) => {
+                problems.push(Problem::error(format!("empty rule for {} method", self.method)).at("rule"));
+            }
+            Method::Regex => {
+                for rule in self.rule.iter().filter(|r| Regex::new(r).is_err()) {
+                    problems.push(Problem::error(format!("invalid regex '{}'", rule)).at(rule));
+                }
+            }
+            Method::FaviconHash => {
+                for rule in self.rule.iter().filter(|r| r.trim().parse::<i64>().is_err()) {
+                    problems.push(Problem::error(format!("faviconhash rule '{}' is not an integer", rule)).at(rule));
+                }
+            }
+            Method::Status => {
+                for rule in self.rule.iter().filter(|r| r.trim().parse::<u16>().is_err()) {
+                    problems.push(Problem::error(format!("status rule '{}' is not a status code", rule)).at(rule));
+                }
+            }
+            _ => {}
+        }
+        for extractor in self.version_extractors.iter().filter(|e| Regex::new(&e.regex).is_err()) {
+            problems.push(Problem::error(format!("invalid version regex '{}'", extractor.regex)).at(&extractor.regex));
+        }
+    }
+
+    // 预编译正则规则，非法正则直接忽略
+    fn prepare(&mut self) {
+        if Method::from_str(&self.method) == Method::Regex {
+            self.regexes = self.rule.iter().filter_map(|r| match Regex::new(r) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    outprint::Print::errprint(format!("Invalid fingerprint regex for {}: {}", self.cms, e).as_str());
+                    None
+                }
+            }).collect();
+        }
+        for condition in &mut self.conditions {
+            condition.prepare();
+        }
+        for extractor in &mut self.version_extractors {
+            match Regex::new(&extractor.regex) {
+                Ok(re) => extractor.compiled = Some(re),
+                Err(e) => outprint::Print::errprint(format!("Invalid version regex for {}: {}", self.cms, e).as_str()),
+            }
+        }
+    }
+
     // 辅助函数，用于判断规则匹配
-    fn matches_rule(&self, hash_string: &str, headers: &HeaderMap, response_text: &str) -> bool {
+    fn matches_rule(&self, ctx: &MatchContext) -> bool {
+        let method = Method::from_str(&self.method);
+        let hits: Vec<bool> = match method {
+            Method::FaviconHash => self.rule.iter().map(|kw| ctx.hashes.contains(kw)).collect(),
+            Method::Keyword => self.rule.iter().map(|kw| ctx.contains(&self.location, kw)).collect(),
+            Method::Regex => {
+                let text = ctx.text(&self.location);
+                self.regexes.iter().map(|re| re.is_match(&text)).collect()
+            }
+            Method::Status => self.rule.iter().map(|code| code.trim() == ctx.status.to_string()).collect(),
+            Method::Cookie => self.rule.iter().map(|name| ctx.has_cookie(name)).collect(),
+            Method::Combined => self.conditions.iter().map(|c| c.matches_rule(ctx)).collect(),
+            Method::Unknown => vec![],
+        };
+        if hits.is_empty() {
+            return false;
+        }
         match self.logic.as_str() {
-            "or" => match self.method.as_str() {
-                "faviconhash" => self.rule.iter().any(|kw| hash_string.contains(kw)),
-                "header" => self.rule.iter().any(|kw| {
-                    headers.iter().any(|(key, value)| {
-                        key.as_str().contains(kw) || value.to_str().unwrap_or_default().contains(kw)
-                    })
-                }),
-                _ => self.rule.iter().any(|kw| response_text.contains(kw)),
-            },
-            "and" => match self.method.as_str() {
-                "faviconhash" => self.rule.iter().all(|kw| hash_string.contains(kw)),
-                "header" => self.rule.iter().all(|kw| {
-                    headers.iter().any(|(key, value)| {
-                        key.as_str().contains(kw) || value.to_str().unwrap_or_default().contains(kw)
-                    })
-                }),
-                _ => self.rule.iter().all(|kw| response_text.contains(kw)),
-            },
+            "or" => hits.iter().any(|h| *h),
+            "and" => hits.iter().all(|h| *h),
             _ => false, // 默认逻辑处理为 false
         }
     }
+
+    // regex 规则的第一个捕获组作为版本号
+    fn capture_version(&self, ctx: &MatchContext) -> Option<String> {
+        match Method::from_str(&self.method) {
+            Method::Regex => {
+                let text = ctx.text(&self.location);
+                self.regexes.iter()
+                    .filter_map(|re| re.captures(&text))
+                    .find_map(|caps| caps.get(1).map(|m| m.as_str().to_string()))
+            }
+            Method::Combined => self.conditions.iter().find_map(|c| c.capture_version(ctx)),
+            _ => None,
+        }
+    }
 }
 
-#[allow(dead_code)]
+// 版本提取：在指定位置（或额外请求的 path，如 JS 文件）上执行正则，取指定捕获组
+#[derive(Debug, Clone, Default, Deserialize)]
+struct VersionExtractor {
+    #[serde(default = "default_location")]
+    location: String,
+    regex: String,
+    #[serde(default = "default_group")]
+    group: usize,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(skip)]
+    compiled: Option<Regex>,
+}
+fn default_group() -> usize { 1 }
+impl VersionExtractor {
+    fn extract(&self, text: &str) -> Option<String> {
+        let caps = self.compiled.as_ref()?.captures(text)?;
+        caps.get(self.group).map(|m| m.as_str().trim().to_string()).filter(|v| !v.is_empty())
+    }
+}
+
+/// 指纹命中记录，供后续检测按产品与版本筛选
+#[derive(Debug, Clone)]
+pub(crate) struct FingerHit {
+    pub(crate) url: String,
+    pub(crate) product: String,
+    pub(crate) version: Option<String>,
+    pub(crate) vendor: Option<String>,
+    pub(crate) category: Option<String>,
+    pub(crate) cpe: Option<String>,
+}
+impl FingerHit {
+    fn new(url: &str, product: &str) -> Self {
+        FingerHit {
+            url: url.to_string(),
+            product: product.to_string(),
+            version: None,
+            vendor: None,
+            category: None,
+            cpe: None,
+        }
+    }
+    // 控制台输出：产品名 + 版本
+    fn display_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {}", self.product, version),
+            None => self.product.clone(),
+        }
+    }
+    // 文件输出：版本与元数据
+    fn details(&self) -> String {
+        [
+            ("Version", &self.version),
+            ("Vendor", &self.vendor),
+            ("Category", &self.category),
+            ("CPE", &self.cpe),
+        ]
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| format!("{} -> {}", name, v)))
+        .collect::<Vec<_>>()
+        .join(" | ")
+    }
+}
+
+// 指纹匹配所需的响应内容，按 location 划分
+struct MatchContext<'a> {
+    status: u16,
+    title: String,
+    body: &'a str,
+    header_lines: Vec<String>,
+    cookies: Vec<String>,
+    hashes: &'a [String],
+}
+impl<'a> MatchContext<'a> {
+    fn new(status: u16, headers: &HeaderMap, body: &'a str, hashes: &'a [String]) -> Self {
+        let header_lines = headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key.as_str(), String::from_utf8_lossy(value.as_bytes())))
+            .collect();
+        let cookies = headers
+            .get_all(reqwest::header::SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| v.split(';').next())
+            .map(|v| v.trim().to_string())
+            .collect();
+        MatchContext {
+            status,
+            title: extract_title(body).unwrap_or_default(),
+            body,
+            header_lines,
+            cookies,
+            hashes,
+        }
+    }
+    fn text(&self, location: &str) -> Cow<'_, str> {
+        match location {
+            "title" => Cow::Borrowed(self.title.as_str()),
+            "header" => Cow::Owned(self.header_lines.join("\n")),
+            "cookie" => Cow::Owned(self.cookies.join("; ")),
+            _ => Cow::Borrowed(self.body),
+        }
+    }
+    fn contains(&self, location: &str, kw: &str) -> bool {
+        match location {
+            "header" => {
+                // 头部统一按小写比较，兼容库中大小写不一的规则
+                let kw = kw.to_lowercase();
+                // 兼容 "X-Powered-By:PigCms.com" 这类冒号后无空格的写法
+                let compact = kw.replacen(": ", ":", 1);
+                self.header_lines.iter().map(|line| line.to_lowercase()).any(|line| line.contains(&kw) || line.replacen(": ", ":", 1).contains(&compact))
+            }
+            "cookie" => self.cookies.iter().any(|cookie| cookie.contains(kw)),
+            _ => self.text(location).contains(kw),
+        }
+    }
+    // 规则为 cookie 名，或 "name=value" 前缀
+    fn has_cookie(&self, rule: &str) -> bool {
+        let rule = rule.trim();
+        self.cookies.iter().any(|cookie| {
+            let name = cookie.split('=').next().unwrap_or_default();
+            name.eq_ignore_ascii_case(rule) || (rule.contains('=') && cookie.starts_with(rule))
+        })
+    }
+}
+
+// 提取 HTML 中的 <title>
+fn extract_title(html_text: &str) -> Option<String> {
+    let document = Html::parse_document(html_text);
+    let title_selector = Selector::parse("title").unwrap();
+    document
+        .select(&title_selector)
+        .next()
+        .map(|title_element| title_element.text().collect::<Vec<_>>().join(""))
+}
+
+#[derive(Debug, PartialEq)]
 enum Method {
     FaviconHash,
-    Header,
-    Body,
-    Title,
+    Keyword,
+    Regex,
+    Status,
+    Cookie,
+    Combined,
     Unknown,
 }
-#[allow(dead_code)]
 impl Method {
-    #[allow(dead_code)]
     fn from_str(method: &str) -> Self {
         match method {
             "faviconhash" => Method::FaviconHash,
-            "header" => Method::Header,
-            "body" => Method::Body,
-            "title" => Method::Body,
+            "keyword" => Method::Keyword,
+            "regex" => Method::Regex,
+            "status" => Method::Status,
+            "cookie" => Method::Cookie,
+            "combined" => Method::Combined,
             _ => Method::Unknown,
         }
     }
@@ -85,6 +367,49 @@ impl Finger {
             finger: Vec::new(),
         }
     }
+    // 逐条校验加载，跳过有错误的指纹；同一产品下重复的规则只给出警告
+    fn load(content: &str) -> (Self, Vec<Issue>) {
+        let seen: RefCell<Vec<FingerPrint>> = RefCell::new(Vec::new());
+        let lint = |fp: &FingerPrint| {
+            let mut problems = Vec::new();
+            if fp.cms.trim().is_empty() {
+                problems.push(Problem::error("empty cms"));
+            }
+            fp.lint(&mut problems);
+            let mut seen = seen.borrow_mut();
+            if seen.iter().any(|f| f.cms.trim().eq_ignore_ascii_case(fp.cms.trim()) && f.same_rule(fp)) {
+                problems.push(Problem::warning("duplicate rule for the same cms"));
+            } else {
+                seen.push(fp.clone());
+            }
+            problems
+        };
+        let (finger, issues) = validate::load_entries(content, Format::Json, "finger", |fp: &FingerPrint| fp.cms.clone(), lint, false);
+        (Finger { finger }, issues)
+    }
+    fn prepare(&mut self) {
+        for fp in &mut self.finger {
+            fp.prepare();
+        }
+    }
+    // 按产品名（忽略大小写）合并导入的指纹，跳过完全重复的规则，返回新增数量
+    fn merge(&mut self, imported: Vec<FingerPrint>) -> usize {
+        let mut added = 0;
+        for mut fp in imported {
+            let key = fp.cms.trim().to_lowercase();
+            let same_product: Vec<&FingerPrint> = self.finger.iter().filter(|f| f.cms.trim().to_lowercase() == key).collect();
+            if same_product.iter().any(|f| f.same_rule(&fp)) {
+                continue;
+            }
+            // 沿用已有指纹的产品名写法
+            if let Some(existing) = same_product.first() {
+                fp.cms = existing.cms.clone();
+            }
+            self.finger.push(fp);
+            added += 1;
+        }
+        added
+    }
 
 }
 struct Rescraw{
@@ -176,15 +501,136 @@ impl Resulturls {
         self.urls.clone()
     }
 }
-fn calculate_hash_as_number(bytes: &[u8]) -> u32 {
-    // 创建 SHA-256 哈希上下文
-    let mut context = Context::new(&SHA256);
-    context.update(bytes);
-    let digest: Digest = context.finish();
+// MurmurHash3 x86_32，与 Python mmh3.hash 结果一致（有符号 32 位）
+fn murmur3_32(data: &[u8], seed: u32) -> i32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let mut h1 = seed;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k1 = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k1 = k1.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        h1 ^= k1;
+        h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
 
-    // 提取哈希的前 4 个字节，并将其作为一个 u32 编码
-    let hash_bytes = &digest.as_ref()[..4]; // 取前 4 个字节
-    u32::from_be_bytes(hash_bytes.try_into().unwrap()) // 转换为 u32
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (i, b) in tail.iter().enumerate() {
+            k1 |= (*b as u32) << (8 * i);
+        }
+        k1 = k1.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        h1 ^= k1;
+    }
+
+    h1 ^= data.len() as u32;
+    h1 ^= h1 >> 16;
+    h1 = h1.wrapping_mul(0x85eb_ca6b);
+    h1 ^= h1 >> 13;
+    h1 = h1.wrapping_mul(0xc2b2_ae35);
+    h1 ^= h1 >> 16;
+    h1 as i32
+}
+
+// 计算 FOFA/Shodan 兼容的 favicon hash：mmh3(base64 每 76 字符换行)
+fn favicon_hash(bytes: &[u8]) -> i32 {
+    let encoded = STANDARD.encode(bytes);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+    for line in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(std::str::from_utf8(line).unwrap_or_default());
+        wrapped.push('\n');
+    }
+    murmur3_32(wrapped.as_bytes(), 0)
+}
+
+// 从主页 HTML 中提取 <link rel="icon"> 等图标地址，并补充默认的 /favicon.ico
+fn favicon_links(base_url: &Url, html_text: &str) -> Vec<String> {
+    let document = Html::parse_document(html_text);
+    let link_selector = Selector::parse("link[rel][href]").unwrap();
+    let mut links = Vec::new();
+    for element in document.select(&link_selector) {
+        let rel = element.value().attr("rel").unwrap_or_default().to_lowercase();
+        // 覆盖 icon / shortcut icon / apple-touch-icon 等写法
+        if !rel.split_whitespace().any(|r| r == "icon" || r.ends_with("-icon")) {
+            continue;
+        }
+        let href = element.value().attr("href").unwrap_or_default().trim();
+        if href.is_empty() {
+            continue;
+        }
+        let link = if href.starts_with("data:") {
+            href.to_string()
+        } else {
+            match base_url.join(href) {
+                Ok(u) => u.to_string(),
+                Err(_) => continue,
+            }
+        };
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    if let Ok(default_icon) = base_url.join("/favicon.ico") {
+        let default_icon = default_icon.to_string();
+        if !links.contains(&default_icon) {
+            links.push(default_icon);
+        }
+    }
+    links
+}
+
+// 解析 data: URI 中的图标内容
+fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    let (meta, data) = uri.strip_prefix("data:")?.split_once(',')?;
+    if !meta.starts_with("image/") {
+        return None;
+    }
+    if meta.ends_with(";base64") {
+        STANDARD.decode(data.trim()).ok()
+    } else {
+        Some(percent_decode(data))
+    }
+}
+
+// 非 base64 的 data: URI 内容为 URL 编码，如 %3Csvg...
+fn percent_decode(data: &str) -> Vec<u8> {
+    let bytes = data.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match data.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) if bytes[i] == b'%' => {
+                out.push(b);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// 判断响应是否为图片，过滤 404 页面、登录页跳转等非图标响应
+fn is_image_response(content_type: Option<&str>, bytes: &[u8]) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+    match content_type.map(|ct| ct.to_lowercase()) {
+        Some(ct) if ct.starts_with("image/") => true,
+        Some(ct) if !ct.starts_with("application/octet-stream") => false,
+        // 未声明或 octet-stream 时根据文件头判断
+        _ => {
+            bytes.starts_with(&[0x00, 0x00, 0x01, 0x00])
+                || bytes.starts_with(b"\x89PNG")
+                || bytes.starts_with(b"GIF8")
+                || bytes.starts_with(&[0xff, 0xd8, 0xff])
+                || bytes.starts_with(b"<svg")
+        }
+    }
 }
 
 
@@ -193,6 +639,7 @@ struct Cmsck {
     client: Arc<Client>,
     not_found: Arc<NotFound>,
     ok_list: Arc<Mutex<Vec<String>>>,
+    finger_hits: Arc<Mutex<Vec<FingerHit>>>,
     bypass_list: Arc<Bypass>,
     unauthorized_list: Arc<Unauthorized>,
 
@@ -219,6 +666,41 @@ impl Cmsck {
         Ok(self.client.get(url).send().await?)
     }
 
+    /// 获取页面声明的所有图标并计算 hash
+    async fn favicon_hashes(&self, base_url: &Url, html_text: &str) -> Vec<String> {
+        let mut hashes = Vec::new();
+        for link in favicon_links(base_url, html_text) {
+            let bytes = if link.starts_with("data:") {
+                match decode_data_uri(&link) {
+                    Some(bytes) => bytes,
+                    None => continue,
+                }
+            } else {
+                let response = match self.html_response(&link).await {
+                    Ok(res) if res.status().is_success() => res,
+                    _ => continue,
+                };
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+                match response.bytes().await {
+                    Ok(bytes) if is_image_response(content_type.as_deref(), &bytes) => bytes.to_vec(),
+                    _ => continue,
+                }
+            };
+            if bytes.is_empty() {
+                continue;
+            }
+            let hash_string = favicon_hash(&bytes).to_string();
+            if !hashes.contains(&hash_string) {
+                hashes.push(hash_string);
+            }
+        }
+        hashes
+    }
+
     async fn ckhtml(&self,url:&str,status: &u64, html_text: &str,filename:&str,ip:Option<&str>) -> Result<(), Box<dyn Error + Send + Sync>> {
         // let document = Html::parse_document(&html_text); // 解析 HTML 文档
         // let title_selector = Selector::parse("title").unwrap_or_else(|_| Selector::parse("*").unwrap());
@@ -247,34 +729,82 @@ impl Cmsck {
         status_as_u64: &u64,
         domain: &str,
         fingerprints: &Finger,
-        hash_string: String,
+        hashes: Vec<String>,
         headers: HeaderMap,
         filename:&str
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let len_as_u64 = response_text.len() as u64;
         self.ckhtml(final_url, status_as_u64, response_text,&filename,None).await?;
-        let mut qc_list = vec![];
+        self.print_fingerprints(domain, final_url, status_as_u64, response_text, fingerprints, &hashes, &headers, filename).await;
+        Ok(()) // 修复：将小写的 ok(()) 改为大写的 Ok(())
+    }
+    // 提取命中指纹的版本，优先使用 version 规则，其次使用 regex 指纹的捕获组
+    async fn extract_version(&self, url: &str, fp: &FingerPrint, ctx: &MatchContext<'_>) -> Option<String> {
+        for extractor in &fp.version_extractors {
+            let version = match &extractor.path {
+                Some(path) => {
+                    let Some(target) = Url::parse(url).ok().and_then(|u| u.join(path).ok()) else { continue };
+                    let response = match self.client.get(target).send().await {
+                        Ok(res) if res.status().is_success() => res,
+                        _ => continue,
+                    };
+                    let text = response.text().await.unwrap_or_default();
+                    extractor.extract(&text)
+                }
+                None => extractor.extract(&ctx.text(&extractor.location)),
+            };
+            if version.is_some() {
+                return version;
+            }
+        }
+        fp.capture_version(ctx)
+    }
+    // 输出并记录命中的指纹，401/403 等非 200 响应同样参与匹配
+    #[allow(clippy::too_many_arguments)]
+    async fn print_fingerprints(
+        &self,
+        domain: &str,
+        url: &str,
+        status_as_u64: &u64,
+        response_text: &str,
+        fingerprints: &Finger,
+        hashes: &[String],
+        headers: &HeaderMap,
+        filename: &str,
+    ) {
+        let len_as_u64 = response_text.len() as u64;
+        let mut hits: Vec<FingerHit> = vec![];
+        let ctx = MatchContext::new(*status_as_u64 as u16, headers, response_text, hashes);
         for d in &fingerprints.finger {
-            if d.matches_rule(&hash_string, &headers, response_text) {
-                if !qc_list.contains(&d.cms) {
-                    qc_list.push(d.cms.to_string());
-                    outprint::Print::cmsprint(domain, status_as_u64, &len_as_u64, &d.cms);
+            if !d.matches_rule(&ctx) {
+                continue;
+            }
+            if !hits.iter().any(|h| h.product == d.cms) {
+                let mut hit = FingerHit::new(url, &d.cms);
+                hit.version = self.extract_version(url, d, &ctx).await;
+                hit.vendor = d.vendor.clone();
+                hit.category = d.category.clone();
+                hit.cpe = d.cpe.clone();
+                hits.push(hit);
+            }
+            for implied in &d.implies {
+                if !hits.iter().any(|h| &h.product == implied) {
+                    hits.push(FingerHit::new(url, implied));
                 }
             }
         }
-        Ok(()) // 修复：将小写的 ok(()) 改为大写的 Ok(())
+        for hit in &hits {
+            outprint::Print::cmsprint(domain, status_as_u64, &len_as_u64, &hit.display_name());
+            let _ = tofile::finger_save_to_file(filename, &hit.url, &hit.product, &hit.details());
+        }
+        self.finger_hits.lock().await.extend(hits);
     }
     async fn crawing(&self, domain: &str, fingerprints: &Finger,filename: &str,other_sets: &OtherSets) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
         let url = domain;
-        let hash_url = format!("{}/favicon.ico", &url);
         let response = self.html_response(&url).await?;
-        let response_hash = self.html_response(&hash_url).await?;
         let status = response.status();
         let headers = response.headers().clone();
+        let resp_url = response.url().clone();
         let response_text = response.text().await?;
-        let bytes = response_hash.bytes().await?;
-        let hash_number = calculate_hash_as_number(&bytes);
-        let hash_string = hash_number.to_string();
 
         match status {
             reqwest::StatusCode::OK | reqwest::StatusCode::FOUND => {
@@ -291,11 +821,16 @@ impl Cmsck {
                     let mut ok_list = self.ok_list.lock().await;
                     ok_list.push(final_url.clone());
                 }
+                let hashes = self.favicon_hashes(&resp_url, &response_text).await;
+                for hash_string in &hashes {
+                    // 记录每个 URL 的 favicon hash，便于在搜索引擎中检索 icon_hash
+                    let _ = tofile::favicon_save_to_file(filename, &final_url, hash_string);
+                }
                 // outprint::Print::infoprint("Start your first crawl");
                 // 第一次爬取：获取初始 URL 列表
                 let initial_links = craw::crawmain(&final_url, response_text.as_str(),other_sets).await?;
                 let status_as_u64 = status.as_u16() as u64;
-                self.print_cms_response(&final_url, &response_text, &status_as_u64, domain, fingerprints, hash_string, headers,&filename).await?;
+                self.print_cms_response(&final_url, &response_text, &status_as_u64, domain, fingerprints, hashes, headers,&filename).await?;
 
                 let mut unique_urls = std::collections::HashSet::new();
                 let mut rescraw_list = Vec::new();
@@ -344,11 +879,13 @@ impl Cmsck {
                 Ok(vec![])
             }
             reqwest::StatusCode::FORBIDDEN => {
+                self.print_fingerprints(domain, url, &(status.as_u16() as u64), &response_text, fingerprints, &[], &headers, filename).await;
                 self.bypass_list.push(url.to_string()).await;
                 Ok(vec![])
             }
             reqwest::StatusCode::UNAUTHORIZED => {
                 outprint::Print::unauthorizedprint(url);
+                self.print_fingerprints(domain, url, &(status.as_u16() as u64), &response_text, fingerprints, &[], &headers, filename).await;
                 self.unauthorized_list.push(url.to_string()).await;
 
                 Ok(vec![])
@@ -393,15 +930,7 @@ impl Cmsck {
 
     }
     async fn gettitle(&self,html_text: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let document = Html::parse_document(&html_text); // 解析 HTML 文档
-        let title_selector = Selector::parse("title").unwrap_or_else(|_| Selector::parse("*").unwrap());
-        let title = if let Some(title_element) = document.select(&title_selector).next() {
-            title_element.text().collect::<Vec<_>>().join("")
-        } else {
-            "Not found title".to_string()
-
-        };
-        Ok(title)
+        Ok(extract_title(html_text).unwrap_or("Not found title".to_string()))
     }
     pub async fn scan_with_path_t(&self, domain: &str, path: &str, homepage_hash: &[u8], homepage_length: usize,homepage_url:&str,filename:&str) -> Result<(), Box<dyn Error + Send + Sync>> {
         // let client = Arc::new(Client::builder().timeout(Duration::from_secs(10)).danger_accept_invalid_certs(true).build()?);
@@ -469,27 +998,41 @@ impl Cmsck {
 }
 
 
+/// 校验指纹文件内容，返回有效条目数与问题列表
+pub(crate) fn validate_fingers(content: &str) -> (usize, Vec<Issue>) {
+    let (fingers, issues) = Finger::load(content);
+    (fingers.finger.len(), issues)
+}
+
 pub async fn cmsmain(filename:&str,threads: usize,client: Client,domains: Vec<String>,mut ip_list:Vec<String>,otherset:OtherSets) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let file_content = include_str!("../config/finger.json");
-    let fingerprints: Finger = match serde_json::from_str(&file_content) {
-        Ok(fingerprints) => fingerprints,
-        Err(e) => {
-            outprint::Print::bannerprint(&format!("Error parsing JSON: {}", e));
-            return Err(Box::new(e)); // 返回错误
+    let file_content = resource::load(Resource::Fingers)?;
+    let (mut fingerprints, issues) = Finger::load(&file_content);
+    validate::report_skipped(&resource::source(Resource::Fingers), &issues);
+    for path in resource::fingerprint_imports() {
+        match fingerimport::import_path(path) {
+            Ok(imported) => {
+                let total = imported.len();
+                let added = fingerprints.merge(imported);
+                outprint::Print::infoprint(format!("Imported {} fingerprints from {} ({} new)", total, path.display(), added).as_str());
+            }
+            Err(e) => outprint::Print::errprint(format!("Failed to import fingerprints from {}: {}", path.display(), e).as_str()),
         }
-    };
+    }
+    fingerprints.prepare();
     let fingerprints = Arc::new(fingerprints);
     let c = client.clone();
     let client = Arc::new(client);
     let otherset = Arc::new(otherset);
     let not_found = Arc::new(NotFound::new());
     let ok_list = Arc::new(Mutex::new(Vec::new()));
+    let finger_hits = Arc::new(Mutex::new(Vec::new()));
     let bypass_list = Arc::new(Bypass::new());
     let unauthorized_list = Arc::new(Unauthorized::new());
     let crawer = Cmsck {
         client: Arc::clone(&client),
         not_found: Arc::clone(&not_found),
         ok_list: Arc::clone(&ok_list),
+        finger_hits: Arc::clone(&finger_hits),
         bypass_list: Arc::clone(&bypass_list),
         unauthorized_list: Arc::clone(&unauthorized_list),
     };
@@ -557,7 +1100,12 @@ pub async fn cmsmain(filename:&str,threads: usize,client: Client,domains: Vec<St
     }
     let _ = tofile::other_save_to_file(&filename,"\n[VULNS INFO]");
     let pocs_req_domains = merge_and_deduplicate(ok_list_urls.clone(), not_found_urls.clone(), bypass_urls.clone(),unauthorized_urls.clone());
-    pocsmain(pocs_req_domains, c.clone(),filename).await?;
+    // 按 URL 汇总命中的指纹，用于筛选 POC
+    let mut url_products: HashMap<String, Vec<String>> = HashMap::new();
+    for hit in finger_hits.lock().await.iter() {
+        url_products.entry(hit.url.trim_end_matches('/').to_string()).or_default().push(hit.product.clone());
+    }
+    pocsmain(pocs_req_domains, c.clone(),filename, &url_products, &otherset).await?;
 
 
     outprint::Print::infoprint("Yaml pocs execution ends");
@@ -574,7 +1122,7 @@ pub async fn cmsmain(filename:&str,threads: usize,client: Client,domains: Vec<St
         // let paths = Arc::new(include_str!("../dict/path.txt").lines().map(String::from).collect::<Vec<_>>());
         let mut ok_list_tasks = Vec::new();
         let paths = Arc::new(
-            include_str!("../dict/path.txt")
+            resource::load(Resource::Paths)?
                 .lines()
                 .map(String::from)
                 .collect::<Vec<_>>(),
@@ -622,7 +1170,7 @@ pub async fn cmsmain(filename:&str,threads: usize,client: Client,domains: Vec<St
     if !not_found_urls.is_empty() {
         outprint::Print::infoprint("Start enumerating 404 response paths");
         // let contents = fs::read_to_string("dict/path.txt")?;
-        let contents = include_str!("../dict/path.txt");
+        let contents = resource::load(Resource::Paths)?;
         let lines: Vec<String> = contents.lines().map(|s| s.to_string()).collect();
         let mut notfound_tasks = vec![];
         let filename = Arc::new(filename.to_string());
@@ -708,6 +1256,388 @@ pub async fn cmsmain(filename:&str,threads: usize,client: Client,domains: Vec<St
 
     outprint::Print::bannerprint(format!("URL saving address with parameters: {} ",filename).as_str());
     outprint::Print::infoprint("Start detecting parameter vulnerabilities");
-    let _ = vulns::vulnmain(threads,c,res).await;
+    let mut forms = craw::forms();
+    forms.retain(|form| !pass_domain.iter().any(|domain| form.action.contains(domain)));
+    let _ = vulns::vulnmain(threads,c,res,forms).await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn favicon_links_cover_rel_variants_and_fallback() {
+        let base = Url::parse("http://example.com/app/index.html").unwrap();
+        let html = r#"<head>
+<link rel="shortcut icon" href="static/fav.png">
+<link rel="apple-touch-icon" href="https://cdn.example.net/touch.png">
+<link rel="icon" href="/favicon.ico">
+<link rel="stylesheet" href="/style.css">
+<link rel="icon" href="data:image/png;base64,iVBORw0KGgo=">
+</head>"#;
+        assert_eq!(
+            favicon_links(&base, html),
+            vec![
+                "http://example.com/app/static/fav.png",
+                "https://cdn.example.net/touch.png",
+                "http://example.com/favicon.ico",
+                "data:image/png;base64,iVBORw0KGgo=",
+            ]
+        );
+        assert_eq!(favicon_links(&base, "<html></html>"), vec!["http://example.com/favicon.ico"]);
+    }
+
+    #[test]
+    fn data_uris_are_decoded() {
+        assert_eq!(decode_data_uri("data:image/png;base64,iVBORw0KGgo="), Some(b"\x89PNG\r\n\x1a\n".to_vec()));
+        assert_eq!(decode_data_uri("data:image/svg+xml,%3Csvg%20xmlns%3D%22x%22%2F%3E"), Some(b"<svg xmlns=\"x\"/>".to_vec()));
+        assert_eq!(decode_data_uri("data:text/html,<p>hi</p>"), None);
+        assert_eq!(decode_data_uri("/favicon.ico"), None);
+    }
+
+    #[test]
+    fn non_image_responses_are_rejected() {
+        assert!(!is_image_response(Some("text/html; charset=utf-8"), b"<html><body>Not Found</body></html>"));
+        assert!(!is_image_response(Some("image/x-icon"), b""));
+        assert!(is_image_response(Some("image/x-icon"), &[0x00, 0x00, 0x01, 0x00, 0x01]));
+        assert!(is_image_response(Some("application/octet-stream"), b"\x89PNG\r\n"));
+        assert!(is_image_response(None, b"GIF89a"));
+        assert!(!is_image_response(None, b"<!DOCTYPE html>"));
+    }
+
+    fn load_fingers() -> Finger {
+        serde_json::from_str(include_str!("../config/finger.json")).expect("finger.json should parse")
+    }
+
+    fn find<'a>(fingers: &'a Finger, cms: &str, location: &str) -> &'a FingerPrint {
+        fingers
+            .finger
+            .iter()
+            .find(|f| f.cms == cms && f.location == location)
+            .unwrap_or_else(|| panic!("fingerprint {} ({}) not found", cms, location))
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (k, v) in pairs {
+            map.append(*k, HeaderValue::from_str(v).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn title_rule_only_matches_title() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "致远OA M1 Server", "title");
+        let empty = HeaderMap::new();
+
+        let in_title = "<html><head><title>M1-Server 已启动</title></head><body></body></html>";
+        assert!(fp.matches_rule(&MatchContext::new(200, &empty, in_title, &[])));
+
+        let in_body = "<html><head><title>Welcome</title></head><body>M1-Server</body></html>";
+        assert!(!fp.matches_rule(&MatchContext::new(200, &empty, in_body, &[])));
+    }
+
+    #[test]
+    fn title_rule_with_and_logic_requires_all_keywords() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "管理后台登录", "title");
+        let empty = HeaderMap::new();
+
+        let both = "<title>管理员登录</title>";
+        assert!(fp.matches_rule(&MatchContext::new(200, &empty, both, &[])));
+
+        let one = "<title>管理员</title><body>登录</body>";
+        assert!(!fp.matches_rule(&MatchContext::new(200, &empty, one, &[])));
+    }
+
+    #[test]
+    fn header_rule_ignores_body_and_case() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "Apache-Shiro", "header");
+
+        let hit = headers(&[("set-cookie", "rememberMe=deleteMe; Path=/")]);
+        assert!(fp.matches_rule(&MatchContext::new(200, &hit, "", &[])));
+
+        let miss = HeaderMap::new();
+        assert!(!fp.matches_rule(&MatchContext::new(200, &miss, "rememberMe=", &[])));
+
+        let solr = find(&fingers, "Apache Solr", "header");
+        let redirect = headers(&[("location", "/solr/")]);
+        assert!(solr.matches_rule(&MatchContext::new(200, &redirect, "", &[])));
+
+        let pig = find(&fingers, "PigCms", "header");
+        let powered = headers(&[("x-powered-by", "PigCms.com")]);
+        assert!(pig.matches_rule(&MatchContext::new(200, &powered, "", &[])));
+    }
+
+    #[test]
+    fn header_rule_with_or_logic_matches_any_keyword() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "Cloudflare", "header");
+
+        let ray = headers(&[("cf-ray", "7d1f")]);
+        assert!(fp.matches_rule(&MatchContext::new(200, &ray, "", &[])));
+
+        let other = headers(&[("server", "nginx")]);
+        assert!(!fp.matches_rule(&MatchContext::new(200, &other, "", &[])));
+    }
+
+    #[test]
+    fn body_rule_with_and_logic() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "致远A6+协同管理软件", "body");
+        let empty = HeaderMap::new();
+
+        let both = "<img src=\"/seeyon/common/images/A6/logo.png\"><a href=\"/autoinstall/A6\">";
+        assert!(fp.matches_rule(&MatchContext::new(200, &empty, both, &[])));
+
+        let one = "<img src=\"/seeyon/common/images/A6/logo.png\">";
+        assert!(!fp.matches_rule(&MatchContext::new(200, &empty, one, &[])));
+    }
+
+    #[test]
+    fn faviconhash_rule_matches_exact_hash() {
+        let fingers = load_fingers();
+        let fp = find(&fingers, "致远互联-协同数据分析云", "body");
+        let empty = HeaderMap::new();
+
+        assert!(fp.matches_rule(&MatchContext::new(200, &empty, "", &["410106848".to_string()])));
+        assert!(!fp.matches_rule(&MatchContext::new(200, &empty, "410106848", &["-410106848".to_string()])));
+    }
+
+    fn parse_finger(json: &str) -> FingerPrint {
+        let mut fp: FingerPrint = serde_json::from_str(json).expect("fingerprint should parse");
+        fp.prepare();
+        fp
+    }
+
+    #[test]
+    fn regex_rule_captures_version() {
+        let fp = parse_finger(r#"{"cms": "nginx", "method": "regex", "location": "header", "rule": ["server: nginx/([\\d.]+)"]}"#);
+        let server = headers(&[("server", "nginx/1.18.0")]);
+        let ctx = MatchContext::new(200, &server, "", &[]);
+        assert!(fp.matches_rule(&ctx));
+        assert_eq!(fp.capture_version(&ctx).as_deref(), Some("1.18.0"));
+
+        let other = headers(&[("server", "Apache")]);
+        assert!(!fp.matches_rule(&MatchContext::new(200, &other, "", &[])));
+    }
+
+    #[test]
+    fn cookie_rule_matches_cookie_name() {
+        let fp = parse_finger(r#"{"cms": "Java", "method": "cookie", "logic": "or", "rule": ["JSESSIONID", "rememberMe"]}"#);
+        let session = headers(&[("set-cookie", "JSESSIONID=ABC123; Path=/; HttpOnly")]);
+        assert!(fp.matches_rule(&MatchContext::new(200, &session, "", &[])));
+
+        let other = headers(&[("set-cookie", "PHPSESSID=JSESSIONID; Path=/")]);
+        assert!(!fp.matches_rule(&MatchContext::new(200, &other, "", &[])));
+    }
+
+    #[test]
+    fn combined_rule_checks_status_and_header() {
+        let fp = parse_finger(r#"{
+            "cms": "Tomcat Manager",
+            "method": "combined",
+            "logic": "and",
+            "conditions": [
+                {"method": "status", "rule": ["401"]},
+                {"method": "keyword", "location": "header", "rule": ["www-authenticate: basic realm=\"tomcat manager application\""]}
+            ]
+        }"#);
+        let auth = headers(&[("www-authenticate", "Basic realm=\"Tomcat Manager Application\"")]);
+        assert!(fp.matches_rule(&MatchContext::new(401, &auth, "", &[])));
+        assert!(!fp.matches_rule(&MatchContext::new(200, &auth, "", &[])));
+    }
+
+    #[test]
+    fn missing_fields_default_to_keyword_body() {
+        let fp = parse_finger(r#"{"cms": "Demo", "rule": ["demo-app"]}"#);
+        assert_eq!(fp.method, "keyword");
+        assert_eq!(fp.location, "body");
+        assert!(fp.matches_rule(&MatchContext::new(200, &HeaderMap::new(), "<div>demo-app</div>", &[])));
+    }
+
+    fn import(json: &str) -> Finger {
+        let mut fingers = Finger::new();
+        fingers.merge(fingerimport::import_str(json).expect("import should succeed"));
+        fingers.prepare();
+        fingers
+    }
+
+    fn matched(fingers: &Finger, ctx: &MatchContext) -> Vec<String> {
+        fingers.finger.iter().filter(|f| f.matches_rule(ctx)).map(|f| f.cms.clone()).collect()
+    }
+
+    #[test]
+    fn import_ehole_requires_all_keywords() {
+        let fingers = import(r#"{"fingerprint": [
+            {"cms": "seeyon", "method": "keyword", "location": "body", "keyword": ["/seeyon/USER-DATA/", "/seeyon/common/"]},
+            {"cms": "Shiro", "method": "keyword", "location": "header", "keyword": ["rememberMe="]}
+        ]}"#);
+        let empty = HeaderMap::new();
+        assert!(matched(&fingers, &MatchContext::new(200, &empty, "/seeyon/common/", &[])).is_empty());
+        assert_eq!(matched(&fingers, &MatchContext::new(200, &empty, "/seeyon/USER-DATA/ /seeyon/common/", &[])), vec!["seeyon"]);
+
+        let cookie = headers(&[("set-cookie", "rememberMe=deleteMe")]);
+        assert_eq!(matched(&fingers, &MatchContext::new(200, &cookie, "", &[])), vec!["Shiro"]);
+    }
+
+    #[test]
+    fn import_wappalyzer_headers_meta_script_and_cookies() {
+        let fingers = import(r#"{
+            "Nginx": {"cats": [22], "headers": {"Server": "nginx(?:/([\\d.]+))?\\;version:\\1"}},
+            "WordPress": {"cats": [1], "meta": {"generator": "^WordPress ?([\\d.]+)?\\;version:\\1"}, "implies": ["PHP", "MySQL"]},
+            "jQuery": {"cats": [59], "scriptSrc": ["jquery(?:-(\\d+\\.\\d+\\.\\d+))[/.-]"]},
+            "Laravel": {"cats": [18], "cookies": {"laravel_session": ""}}
+        }"#);
+
+        let server = headers(&[("server", "nginx/1.24.0")]);
+        let ctx = MatchContext::new(200, &server, "", &[]);
+        let hits: Vec<&FingerPrint> = fingers.finger.iter().filter(|f| f.matches_rule(&ctx)).collect();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].cms, "Nginx");
+        assert_eq!(hits[0].capture_version(&ctx).as_deref(), Some("1.24.0"));
+
+        let empty = HeaderMap::new();
+        let body = r#"<meta name="generator" content="WordPress 6.4"><script src="/js/jquery-3.6.0.min.js"></script>"#;
+        let mut hits = matched(&fingers, &MatchContext::new(200, &empty, body, &[]));
+        hits.sort();
+        assert_eq!(hits, vec!["WordPress", "jQuery"]);
+        let wp = fingers.finger.iter().find(|f| f.cms == "WordPress").unwrap();
+        assert_eq!(wp.implies, vec!["PHP", "MySQL"]);
+
+        let session = headers(&[("set-cookie", "laravel_session=abc; path=/")]);
+        assert_eq!(matched(&fingers, &MatchContext::new(200, &session, "", &[])), vec!["Laravel"]);
+    }
+
+    #[test]
+    fn import_goby_or_of_and_groups() {
+        let fingers = import(r#"[
+            {"product": "Weblogic", "rules": [
+                [{"match": "body_contains", "content": "WebLogic Server"}, {"match": "title_contains", "content": "Error 404"}],
+                [{"match": "header_contains", "content": "x-oracle-dms-ecid"}],
+                [{"match": "cert_contains", "content": "weblogic"}]
+            ]}
+        ]"#);
+        let empty = HeaderMap::new();
+        assert_eq!(matched(&fingers, &MatchContext::new(200, &empty, "<title>Error 404</title>WebLogic Server", &[])), vec!["Weblogic"]);
+        assert!(matched(&fingers, &MatchContext::new(200, &empty, "WebLogic Server", &[])).is_empty());
+
+        let ecid = headers(&[("X-ORACLE-DMS-ECID", "abc")]);
+        assert_eq!(matched(&fingers, &MatchContext::new(200, &ecid, "", &[])), vec!["Weblogic"]);
+    }
+
+    #[test]
+    fn merge_dedupes_by_product_name() {
+        let mut fingers = load_fingers();
+        let before = fingers.finger.len();
+        let added = fingers.merge(fingerimport::import_str(r#"{"fingerprint": [
+            {"cms": "致远OA", "method": "keyword", "location": "body", "keyword": ["/seeyon/common/"]},
+            {"cms": "apache-shiro", "method": "keyword", "location": "header", "keyword": ["shiro-cas"]}
+        ]}"#).unwrap());
+        assert_eq!(added, 1);
+        assert_eq!(fingers.finger.len(), before + 1);
+        assert_eq!(fingers.finger.last().unwrap().cms, "Apache-Shiro");
+    }
+
+    #[test]
+    fn version_extractor_and_metadata() {
+        let fp = parse_finger(r#"{
+            "cms": "ThinkPHP",
+            "method": "keyword",
+            "location": "header",
+            "rule": ["thinkphp"],
+            "version": [
+                {"location": "body", "regex": "ThinkPHP V(\\d+\\.\\d+)"},
+                {"location": "body", "regex": "(?i)think_version\\s*=\\s*'([\\d.]+)'"}
+            ],
+            "vendor": "TopThink",
+            "category": "framework",
+            "cpe": "cpe:2.3:a:thinkphp:thinkphp"
+        }"#);
+        let body = "<script>var think_version = '5.0.23';</script>";
+        let powered = headers(&[("x-powered-by", "ThinkPHP")]);
+        let ctx = MatchContext::new(200, &powered, body, &[]);
+        assert!(fp.matches_rule(&ctx));
+        let version = fp.version_extractors.iter().find_map(|e| e.extract(&ctx.text(&e.location)));
+        assert_eq!(version.as_deref(), Some("5.0.23"));
+
+        let mut hit = FingerHit::new("http://example.com", &fp.cms);
+        hit.version = version;
+        hit.vendor = fp.vendor.clone();
+        hit.category = fp.category.clone();
+        hit.cpe = fp.cpe.clone();
+        assert_eq!(hit.display_name(), "ThinkPHP 5.0.23");
+        assert_eq!(hit.details(), "Version -> 5.0.23 | Vendor -> TopThink | Category -> framework | CPE -> cpe:2.3:a:thinkphp:thinkphp");
+    }
+
+    #[test]
+    fn favicon_hash_is_mmh3_compatible() {
+        assert_eq!(murmur3_32(b"hello", 0), 613153351);
+        assert_eq!(murmur3_32(b"The quick brown fox jumps over the lazy dog", 0), 776992547);
+    }
+
+    #[test]
+    fn favicon_hash_wraps_base64_at_76_chars() {
+        // 100 字节 → 136 个 base64 字符，需要换行，结果与 python mmh3.hash(base64.encodebytes(data)) 一致
+        let data: Vec<u8> = (0..100).collect();
+        assert_eq!(favicon_hash(&data), -1165240594);
+        assert_eq!(favicon_hash(b""), murmur3_32(b"", 0));
+    }
+
+    #[test]
+    fn embedded_fingers_validate() {
+        let (count, issues) = validate_fingers(include_str!("../config/finger.json"));
+        assert!(count > 0);
+        let errors: Vec<String> = issues.iter().filter(|i| i.level == validate::Level::Error).map(|i| i.to_string()).collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn invalid_fingers_are_skipped_with_line_numbers() {
+        let json = r#"{
+  "finger": [
+    {"cms": "ok", "method": "keyword", "location": "body", "rule": ["ok"]},
+    {"cms": "ok", "method": "keyword", "location": "body", "rule": ["ok"]},
+    {
+      "cms": "bad",
+      "method": "regex",
+      "location": "body",
+      "rule": ["(unclosed"]
+    },
+    {"cms": "hash", "method": "faviconhash", "location": "body", "rule": ["abc"]},
+    {"cms": "combo", "method": "combined", "logic": "xor", "conditions": []},
+    {"cms": "where", "method": "keyword", "location": "footer", "rule": ["x"]},
+    {"cms": "schema", "rule": "x"}
+  ]
+}"#;
+        let (fingers, issues) = Finger::load(json);
+        assert_eq!(fingers.finger.len(), 2);
+        let found = |line: usize, text: &str| issues.iter().any(|i| i.line == line && i.message.contains(text));
+        assert!(found(4, "duplicate rule"), "{:?}", issues);
+        assert!(found(9, "invalid regex"), "{:?}", issues);
+        assert!(found(11, "not an integer"), "{:?}", issues);
+        assert!(found(12, "invalid logic"), "{:?}", issues);
+        assert!(found(12, "combined rule without conditions"), "{:?}", issues);
+        assert!(found(13, "unknown location 'footer'"), "{:?}", issues);
+        assert!(found(14, "schema error"), "{:?}", issues);
+    }
+
+    #[test]
+    fn garbage_between_fingers_is_a_syntax_error() {
+        let json = r#"{
+  "finger": [
+    {"cms": "a", "method": "keyword", "location": "body", "rule": ["a"]},
+    oops
+    {"cms": "b", "method": "keyword", "location": "body", "rule": ["b"]}
+  ]
+}"#;
+        let (fingers, issues) = Finger::load(json);
+        assert_eq!(fingers.finger.len(), 2);
+        assert!(issues.iter().any(|i| i.level == validate::Level::Error && i.line == 4 && i.message.starts_with("syntax error")), "{:?}", issues);
+    }
+
+}
+
@@ -13,6 +13,64 @@ static REPORTED_URLS: Lazy<Arc<Mutex<HashSet<String>>>> = Lazy::new(|| {
     Arc::new(Mutex::new(HashSet::new()))
 });
 
+// 爬取过程中发现的表单，供参数漏洞检测使用
+static FORMS: Lazy<Mutex<HashSet<Form>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 页面中的 <form>，action 已转换为绝对地址
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Form {
+    pub method: String,
+    pub action: String,
+    pub enctype: String,
+    pub fields: Vec<(String, String)>,
+}
+
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#"(?i)\s{}\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#, name)).unwrap();
+    re.captures(tag).and_then(|caps| caps.get(1).or(caps.get(2)).or(caps.get(3))).map(|m| m.as_str().to_string())
+}
+
+// 解析表单及其 input / textarea / select 字段，跳过无 name 及提交类按钮
+fn parse_forms(url: &str, html: &str) -> Vec<Form> {
+    let form_pattern = Regex::new(r"(?is)(<form\b[^>]*>)(.*?)</form>").unwrap();
+    let field_pattern = Regex::new(r"(?is)<(input|textarea|select)\b[^>]*>").unwrap();
+    let Ok(base) = reqwest::Url::parse(url) else { return vec![] };
+    let mut forms = vec![];
+    for caps in form_pattern.captures_iter(html) {
+        let tag = &caps[1];
+        let action = attr(tag, "action").unwrap_or_default();
+        let Ok(action) = base.join(action.trim()) else { continue };
+        if !action.scheme().starts_with("http") {
+            continue;
+        }
+        let mut fields = vec![];
+        for field in field_pattern.find_iter(&caps[2]) {
+            let field = field.as_str();
+            let Some(name) = attr(field, "name") else { continue };
+            let kind = attr(field, "type").unwrap_or_default().to_lowercase();
+            if ["submit", "button", "image", "reset", "file"].contains(&kind.as_str()) {
+                continue;
+            }
+            fields.push((name, attr(field, "value").unwrap_or_default()));
+        }
+        if fields.is_empty() {
+            continue;
+        }
+        forms.push(Form {
+            method: attr(tag, "method").unwrap_or_else(|| "GET".to_string()).to_uppercase(),
+            action: action.to_string(),
+            enctype: attr(tag, "enctype").unwrap_or_default().to_lowercase(),
+            fields,
+        });
+    }
+    forms
+}
+
+/// 目前为止爬取到的全部表单
+pub fn forms() -> Vec<Form> {
+    FORMS.lock().unwrap().iter().cloned().collect()
+}
+
 #[allow(dead_code)]
 struct Links{
     urls:Vec<String>,
@@ -145,6 +203,7 @@ pub async fn crawmain(
 ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
     let link_scan = LinkScan;
     let result = link_scan.crawler(url, html).await?;
+    FORMS.lock().unwrap().extend(parse_forms(url, html));
 
     // 获取对全局敏感URL列表的锁
     let mut reported_urls = REPORTED_URLS.lock().unwrap();
@@ -178,4 +237,30 @@ pub async fn crawmain(
         }
     }
     Ok(result)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forms_are_parsed_with_absolute_actions() {
+        let html = r#"<form action="/login" method="post">
+  <input type="text" name="user" value="admin">
+  <input type=password name=pass>
+  <input type="hidden" name='token' value='abc'>
+  <textarea name="note"></textarea>
+  <input type="submit" name="go" value="Login">
+</form>
+<form><input name="q"></form>
+<form action="javascript:void(0)"><input name="x"></form>"#;
+        let forms = parse_forms("http://example.com/app/index.php", html);
+        assert_eq!(forms.len(), 2);
+        assert_eq!(forms[0].method, "POST");
+        assert_eq!(forms[0].action, "http://example.com/login");
+        let names: Vec<&str> = forms[0].fields.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["user", "pass", "token", "note"]);
+        assert_eq!(forms[0].fields[0].1, "admin");
+        assert_eq!(forms[1].method, "GET");
+        assert_eq!(forms[1].action, "http://example.com/app/index.php");
+    }
+}
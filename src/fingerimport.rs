@@ -0,0 +1,238 @@
+// 第三方指纹库导入：EHole / Wappalyzer / Goby 格式转换为 rend 内部指纹
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::cmsck::FingerPrint;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FingerFormat {
+    Rend,
+    EHole,
+    Wappalyzer,
+    Goby,
+}
+
+// EHole finger.json：顶层为 fingerprint，关键字字段为 keyword，全部命中才算匹配
+#[derive(Debug, Deserialize)]
+struct EHoleFinger {
+    fingerprint: Vec<EHoleRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EHoleRule {
+    cms: String,
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    location: String,
+    #[serde(default)]
+    keyword: Vec<String>,
+}
+
+// Goby 风格：rules 外层为 or，内层为 and
+#[derive(Debug, Deserialize)]
+struct GobyRule {
+    #[serde(alias = "name")]
+    product: String,
+    #[serde(alias = "rule")]
+    rules: Vec<Vec<GobyMatch>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GobyMatch {
+    #[serde(rename = "match")]
+    match_type: String,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RendFinger {
+    finger: Vec<FingerPrint>,
+}
+
+/// 根据 JSON 结构判断指纹库格式
+pub fn detect_format(value: &Value) -> Option<FingerFormat> {
+    match value {
+        Value::Object(map) if map.contains_key("finger") => Some(FingerFormat::Rend),
+        Value::Object(map) if map.contains_key("fingerprint") => Some(FingerFormat::EHole),
+        Value::Object(map) if map.contains_key("technologies") => Some(FingerFormat::Wappalyzer),
+        Value::Array(items) if items.iter().all(|i| i.get("rules").or_else(|| i.get("rule")).is_some()) => Some(FingerFormat::Goby),
+        // technologies/*.json：产品名 -> 规则对象
+        Value::Object(map) if !map.is_empty() && map.values().all(|v| v.is_object()) => Some(FingerFormat::Wappalyzer),
+        _ => None,
+    }
+}
+
+/// 转换单个指纹文件内容
+pub fn import_str(content: &str) -> Result<Vec<FingerPrint>, Box<dyn Error + Send + Sync>> {
+    let value: Value = serde_json::from_str(content)?;
+    match detect_format(&value) {
+        Some(FingerFormat::Rend) => Ok(serde_json::from_value::<RendFinger>(value)?.finger),
+        Some(FingerFormat::EHole) => Ok(from_ehole(serde_json::from_value(value)?)),
+        Some(FingerFormat::Goby) => Ok(from_goby(serde_json::from_value(value)?)),
+        Some(FingerFormat::Wappalyzer) => {
+            // 兼容完整 apps.json（含 technologies 字段）与拆分后的 technologies/*.json
+            let techs = match value.get("technologies") {
+                Some(techs) => techs.clone(),
+                None => value,
+            };
+            Ok(from_wappalyzer(&techs))
+        }
+        None => Err("Unknown fingerprint format".into()),
+    }
+}
+
+/// 导入文件或目录（目录下所有 .json 文件）
+pub fn import_path(path: &Path) -> Result<Vec<FingerPrint>, Box<dyn Error + Send + Sync>> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|ext| ext == "json").unwrap_or(false))
+            .collect();
+        entries.sort();
+        let mut fingers = Vec::new();
+        for entry in entries {
+            fingers.extend(import_str(&fs::read_to_string(&entry)?)?);
+        }
+        Ok(fingers)
+    } else {
+        import_str(&fs::read_to_string(path)?)
+    }
+}
+
+fn from_ehole(ehole: EHoleFinger) -> Vec<FingerPrint> {
+    ehole
+        .fingerprint
+        .into_iter()
+        .filter(|r| !r.keyword.is_empty())
+        .map(|r| {
+            let method = if r.method == "faviconhash" { "faviconhash" } else { "keyword" };
+            let location = if r.location.is_empty() { "body" } else { r.location.as_str() };
+            FingerPrint::new(&r.cms, method, location, "and", r.keyword.clone())
+        })
+        .collect()
+}
+
+fn from_goby(rules: Vec<GobyRule>) -> Vec<FingerPrint> {
+    let mut fingers = Vec::new();
+    for rule in rules {
+        let groups: Vec<FingerPrint> = rule
+            .rules
+            .iter()
+            .filter_map(|group| {
+                // 组内存在无法识别的匹配类型时整组跳过，避免误报
+                let conditions: Option<Vec<FingerPrint>> = group
+                    .iter()
+                    .map(|m| {
+                        let location = match m.match_type.as_str() {
+                            "body_contains" => "body",
+                            "title_contains" => "title",
+                            "header_contains" | "banner_contains" | "server" | "server_contains" => "header",
+                            "cookie_contains" => "cookie",
+                            _ => return None,
+                        };
+                        Some(FingerPrint::new("", "keyword", location, "and", vec![m.content.clone()]))
+                    })
+                    .collect();
+                conditions.filter(|c| !c.is_empty())
+            })
+            .map(|conditions| FingerPrint::combined("", "and", conditions))
+            .collect();
+        if !groups.is_empty() {
+            fingers.push(FingerPrint::combined(&rule.product, "or", groups));
+        }
+    }
+    fingers
+}
+
+// Wappalyzer 模式形如 "nginx(?:/([\d.]+))?\;version:\1"，去掉 \; 之后的附加信息
+fn wappalyzer_pattern(pattern: &str) -> String {
+    pattern.split("\\;").next().unwrap_or_default().to_string()
+}
+
+// 将取值模式拼接到固定前缀之后，保留模式自身的 ^ 锚点语义
+fn anchored(prefix: &str, pattern: &str) -> String {
+    match pattern.strip_prefix('^') {
+        Some(rest) => format!("{}{}", prefix, rest),
+        None => format!("{}[^\\n]*?{}", prefix, pattern),
+    }
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().filter_map(|i| i.as_str().map(String::from)).collect(),
+        _ => vec![],
+    }
+}
+
+// 转换后的正则需能被 regex 库编译（Wappalyzer 使用 JS 正则，部分语法不支持）
+fn regex_condition(location: &str, pattern: String) -> Option<FingerPrint> {
+    Regex::new(&pattern).ok()?;
+    Some(FingerPrint::new("", "regex", location, "or", vec![pattern]))
+}
+
+fn from_wappalyzer(techs: &Value) -> Vec<FingerPrint> {
+    let mut fingers = Vec::new();
+    let Some(techs) = techs.as_object() else {
+        return fingers;
+    };
+    for (name, tech) in techs {
+        let mut conditions = Vec::new();
+
+        if let Some(headers) = tech.get("headers").and_then(|h| h.as_object()) {
+            for (header, pattern) in headers {
+                let pattern = wappalyzer_pattern(pattern.as_str().unwrap_or_default());
+                let prefix = format!("(?im)^{}: ", regex::escape(&header.to_lowercase()));
+                conditions.extend(regex_condition("header", anchored(&prefix, &pattern)));
+            }
+        }
+        if let Some(metas) = tech.get("meta").and_then(|m| m.as_object()) {
+            for (meta, pattern) in metas {
+                for pattern in string_list(pattern) {
+                    let prefix = format!(
+                        r#"(?i)<meta[^>]+name=["']{}["'][^>]+content=["']"#,
+                        regex::escape(meta)
+                    );
+                    conditions.extend(regex_condition("body", anchored(&prefix, &wappalyzer_pattern(&pattern))));
+                }
+            }
+        }
+        for pattern in tech.get("scriptSrc").map(string_list).unwrap_or_default() {
+            let prefix = r#"(?i)<script[^>]+src=["']"#;
+            conditions.extend(regex_condition("body", anchored(prefix, &wappalyzer_pattern(&pattern))));
+        }
+        for pattern in tech.get("html").map(string_list).unwrap_or_default() {
+            conditions.extend(regex_condition("body", format!("(?i){}", wappalyzer_pattern(&pattern))));
+        }
+        if let Some(cookies) = tech.get("cookies").and_then(|c| c.as_object()) {
+            for (cookie, pattern) in cookies {
+                let pattern = wappalyzer_pattern(pattern.as_str().unwrap_or_default());
+                if pattern.is_empty() {
+                    conditions.push(FingerPrint::new("", "cookie", "header", "or", vec![cookie.clone()]));
+                } else {
+                    let prefix = format!("(?i)(?:^|; ){}=", regex::escape(cookie));
+                    conditions.extend(regex_condition("cookie", anchored(&prefix, &pattern)));
+                }
+            }
+        }
+
+        if conditions.is_empty() {
+            continue;
+        }
+        let mut finger = FingerPrint::combined(name, "or", conditions);
+        finger.implies = tech
+            .get("implies")
+            .map(string_list)
+            .unwrap_or_default()
+            .iter()
+            .map(|i| wappalyzer_pattern(i))
+            .collect();
+        fingers.push(finger);
+    }
+    fingers
+}
@@ -3,6 +3,8 @@ use crate::tofile;
 use crate::cmsck;
 use crate::port;
 use crate::subdomain;
+use crate::pocscan;
+use crate::oob;
 use async_trait::async_trait;
 use reqwest::{Client, header::{HeaderMap, HeaderName, HeaderValue,ACCEPT, ACCEPT_LANGUAGE, CACHE_CONTROL, CONNECTION, HOST, REFERER, USER_AGENT}};
 use serde::Deserialize;
@@ -23,6 +25,7 @@ use tokio::sync::Semaphore;
 use base64::engine::Engine as _;
 use base64::engine::general_purpose::STANDARD;
 use crate::icpscan::icpmain;
+use crate::resource::{self, Resource};
 
 #[async_trait]
 trait InfoFetcher{
@@ -44,7 +47,31 @@ pub struct OtherSets {
     pub(crate) port_random_min: u16,
     pub(crate) attack_port_number: usize,
     pub(crate) scan_port_max: u64,
+    #[serde(default)]
+    pub(crate) run_all_pocs: bool,
+    #[serde(default)]
+    pub(crate) poc_severity: Vec<String>,
+    #[serde(default)]
+    pub(crate) poc_tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) oob_enabled: bool,
+    #[serde(default = "default_oob_listen")]
+    pub(crate) oob_listen: String,
+    #[serde(default)]
+    pub(crate) oob_public_host: String,
+    #[serde(default = "default_oob_http_port")]
+    pub(crate) oob_http_port: u16,
+    #[serde(default = "default_oob_dns_port")]
+    pub(crate) oob_dns_port: u16,
+    #[serde(default)]
+    pub(crate) oob_domain: String,
+    #[serde(default = "default_oob_wait")]
+    pub(crate) oob_wait: u64,
 }
+fn default_oob_listen() -> String { "0.0.0.0".to_string() }
+fn default_oob_http_port() -> u16 { 8899 }
+fn default_oob_dns_port() -> u16 { 53 }
+fn default_oob_wait() -> u64 { 10 }
 #[derive(Debug, Deserialize)]
 pub struct Config {
     api_keys: ApiKeys,
@@ -124,6 +151,7 @@ struct InfoResults{
     ip_list: Vec<String>,
     icp_list: Vec<String>,
     cdn_list: Vec<String>,
+    bruteforce_list: Vec<subdomain::SubdomainResult>,
 }
 
 #[async_trait]
@@ -160,6 +188,19 @@ impl Displayinfo for InfoResults {
                 }
             }
         }
+        if !self.bruteforce_list.is_empty() {
+            let brute_lines: Vec<String> = self.bruteforce_list.iter().map(|r| {
+                if r.cnames.is_empty() {
+                    format!("{} -> {}", r.hostname, r.ips.join(","))
+                } else {
+                    format!("{} -> {} | CNAME -> {}", r.hostname, r.ips.join(","), r.cnames.join(","))
+                }
+            }).collect();
+            match tofile::bruteforce_save_to_file(&filename, &brute_lines) {
+                Ok(_) => outprint::Print::bannerprint(format!("Bruteforce results saved to {}",&filename).as_str()),
+                Err(e) => outprint::Print::errprint(format!("Failed to save bruteforce results to file: {}", e).as_str()),
+            }
+        }
         outprint::Print::infoprint("Start organizing data");
 
         let mut domain_list = self.domain_list.clone();
@@ -222,6 +263,7 @@ impl InfoResults {
             ip_list:vec![],
             icp_list:vec![],
             cdn_list:vec![],
+            bruteforce_list:vec![],
         }
 
     }
@@ -233,6 +275,7 @@ impl InfoResults {
         self.ip_list.extend(other.ip_list);
         self.icp_list.extend(other.icp_list);
         self.cdn_list.extend(other.cdn_list);
+        self.bruteforce_list.extend(other.bruteforce_list);
     }
     fn clean_all(&mut self) {
         self.domain_list.retain(|x| !x.is_empty());
@@ -1639,7 +1682,7 @@ impl InfoFetcher for InfoC99NL {
         Ok(results)
     }
 }
-async fn build_client(arg: &HashMap<&str, String>) -> Result<Client, Box<dyn std::error::Error>> {
+async fn build_client(arg: &HashMap<&str, String>, follow_redirects: bool) -> Result<Client, Box<dyn std::error::Error>> {
     // 解析 timeout，默认值为 30 秒
     let timeout = arg
         .get("timeout")
@@ -1678,6 +1721,10 @@ async fn build_client(arg: &HashMap<&str, String>) -> Result<Client, Box<dyn std
     // 将解析的 headers 设置到客户端构建器
     client_builder = client_builder.default_headers(headers);
 
+    if !follow_redirects {
+        client_builder = client_builder.redirect(reqwest::redirect::Policy::none());
+    }
+
     // 返回构建的客户端
     Ok(client_builder.build()?)
 }
@@ -1702,8 +1749,28 @@ fn parse_headers(input: &str, headers: &mut HeaderMap) {
     }
 }
 pub async fn infomain(arg: HashMap<&str, String>, domain: &str, custom_config_path: Option<&str>) -> Result<(), Box<dyn Error>> {
-    let other_set_content = include_str!("../config/config.toml");
-    let other_content: OtherSets = toml::from_str(other_set_content)?;
+    let other_set_content = resource::load(Resource::Config)?;
+    let mut other_content: OtherSets = toml::from_str(&other_set_content)?;
+    if arg.contains_key("all_pocs") {
+        other_content.run_all_pocs = true;
+    }
+    // 命令行筛选条件覆盖配置文件，逗号分隔
+    let split_list = |s: &String| s.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect::<Vec<_>>();
+    if let Some(severity) = arg.get("poc_severity") {
+        other_content.poc_severity = split_list(severity);
+    }
+    if let Some(tags) = arg.get("poc_tags") {
+        other_content.poc_tags = split_list(tags);
+    }
+    if arg.contains_key("oob") {
+        other_content.oob_enabled = true;
+    }
+    if let Some(host) = arg.get("oob_host") {
+        other_content.oob_public_host = host.clone();
+    }
+    if let Some(domain) = arg.get("oob_domain") {
+        other_content.oob_domain = domain.clone();
+    }
 
     let mut config = Config::from_default()?;
     if let Some(path) = custom_config_path {
@@ -1712,7 +1779,13 @@ pub async fn infomain(arg: HashMap<&str, String>, domain: &str, custom_config_pa
     }
     let api_keys = config.api_keys;
 
-    let client = build_client(&arg).await?;
+    let client = build_client(&arg, true).await?;
+    // yaml poc 需要匹配 30x 响应时使用
+    pocscan::init_no_redirect_client(build_client(&arg, false).await?);
+    // 带外回连监听，启动失败时仅关闭 OOB 检测
+    if let Err(e) = oob::init(&other_content).await {
+        outprint::Print::errprint(format!("OOB listener failed: {}", e).as_str());
+    }
     let fetchers: Vec<Arc<dyn InfoFetcher + Send + Sync>> = vec![
         Arc::new(InfoFofa),
         Arc::new(InfoQuake),
@@ -1768,7 +1841,21 @@ pub async fn infomain(arg: HashMap<&str, String>, domain: &str, custom_config_pa
     for target_domain in domains.iter() {
         outprint::Print::infoprint(&format!("Processing domain: {}", target_domain));
         outprint::Print::infoprint("Start enumerating subdomains");
-        let _ = subdomain::scan_subdomains(target_domain, threads).await;
+        match subdomain::scan_subdomains(target_domain, threads).await {
+            Ok(found) => {
+                // 爆破结果并入主流程，参与端口、指纹及漏洞检测
+                let mut results = InfoResults::new();
+                for r in &found {
+                    results.domain_list.push(r.hostname.clone());
+                    results.ip_list.extend(r.ips.iter().cloned());
+                }
+                results.bruteforce_list = found;
+                results.clean_all();
+                outprint::Print::infoprint(format!("Bruteforce found Domain {} | found IP {}",results.domain_list.len(), results.ip_list.len()).as_str());
+                combined_results.lock().await.merge(results);
+            }
+            Err(e) => outprint::Print::errprint(format!("Bruteforce error for {}: {}", target_domain, e).as_str()),
+        }
         outprint::Print::infoprint("End of subdomain enumeration");
         outprint::Print::infoprint("Start information collection");
 
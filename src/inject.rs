@@ -0,0 +1,370 @@
+// 参数漏洞检测的注入点：查询参数、RESTful 路径段、表单字段、JSON 键、请求头及 Cookie
+use std::fmt;
+use std::ops::Range;
+use regex::Regex;
+use reqwest::header::{CONTENT_TYPE, SET_COOKIE};
+use reqwest::{Client, Method, RequestBuilder};
+use serde_json::Value;
+use crate::craw::Form;
+
+// 会被注入的请求头，只在变异请求中发送，其余请求沿用客户端自身的请求头
+const HEADERS: &[&str] = &["User-Agent", "Referer", "X-Forwarded-For"];
+
+// 形如 ID 的路径段：数字、UUID 或较长的十六进制串
+const PATH_ID: &str = r"^(\d+|[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}|[0-9a-fA-F]{16,})$";
+
+// 参数值中的保留字符需要编码，避免 & # 等破坏查询串
+pub(crate) fn encode_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// 还原 URL 编码，JSON 与请求头中使用服务端实际收到的值
+fn decode_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = value.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Body {
+    Form,
+    Json,
+}
+
+/// 被测请求：方法、URL、请求体及附加请求头
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Target {
+    pub(crate) method: Method,
+    pub(crate) url: String,
+    body: Option<(Body, String)>,
+    headers: Vec<(String, String)>,
+    // 是否把 HEADERS 作为注入点
+    header_points: bool,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.method {
+            Method::GET => write!(f, "{}", self.url),
+            _ => write!(f, "{} {}", self.method, self.url),
+        }
+    }
+}
+
+impl Target {
+    pub(crate) fn get(url: &str) -> Self {
+        Target { method: Method::GET, url: url.to_string(), body: None, headers: vec![], header_points: false }
+    }
+
+    // GET 表单拼接到查询串；其余方法按 enctype 生成 urlencoded 或 JSON 请求体
+    pub(crate) fn from_form(form: &Form) -> Self {
+        let action = form.action.split('#').next().unwrap_or_default();
+        let encoded = form.fields.iter()
+            .map(|(k, v)| format!("{}={}", encode_value(k), encode_value(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let method = Method::from_bytes(form.method.as_bytes()).unwrap_or(Method::POST);
+        if method == Method::GET {
+            let separator = if action.contains('?') { '&' } else { '?' };
+            return Target::get(&format!("{}{}{}", action, separator, encoded));
+        }
+        let body = if form.enctype.contains("json") {
+            let object: serde_json::Map<String, Value> = form.fields.iter().map(|(k, v)| (k.clone(), Value::String(v.clone()))).collect();
+            (Body::Json, Value::Object(object).to_string())
+        } else {
+            (Body::Form, encoded)
+        };
+        Target { method, url: action.to_string(), body: Some(body), headers: vec![], header_points: false }
+    }
+
+    /// 把常见请求头加入注入点
+    pub(crate) fn with_headers(mut self) -> Self {
+        self.header_points = true;
+        self
+    }
+
+    // 追加模式下请求头的原值：Referer 为目标地址，其余为空
+    fn header_base(&self, name: &str) -> String {
+        if name == "Referer" { self.url.clone() } else { String::new() }
+    }
+
+    /// 请求一次目标，把响应设置的 Cookie 作为注入点带上
+    pub(crate) async fn with_cookies(mut self, client: &Client) -> Self {
+        let Ok(res) = self.request(client).send().await else { return self };
+        let cookies: Vec<String> = res.headers().get_all(SET_COOKIE).iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| v.split(';').next())
+            .filter(|pair| pair.contains('='))
+            .map(|pair| pair.trim().to_string())
+            .collect();
+        if !cookies.is_empty() {
+            self.headers.push(("Cookie".to_string(), cookies.join("; ")));
+        }
+        self
+    }
+
+    pub(crate) fn request(&self, client: &Client) -> RequestBuilder {
+        let mut req = client.request(self.method.clone(), &self.url);
+        for (name, value) in &self.headers {
+            req = req.header(name.as_str(), value.as_str());
+        }
+        match &self.body {
+            Some((Body::Form, body)) => req.header(CONTENT_TYPE, "application/x-www-form-urlencoded").body(body.clone()),
+            Some((Body::Json, body)) => req.header(CONTENT_TYPE, "application/json").body(body.clone()),
+            None => req,
+        }
+    }
+
+    /// 请求中全部可注入的位置
+    pub(crate) fn points(&self) -> Vec<Point> {
+        let mut points = vec![];
+        let point = |location, name: &str, value: Range<usize>| Point { location, name: name.to_string(), value, header: 0, pointer: String::new() };
+
+        let query = Regex::new(r"([?&])([^=]+)=([^&]*)").unwrap();
+        for caps in query.captures_iter(&self.url) {
+            points.push(point(Location::Query, &caps[2], caps.get(3).unwrap().range()));
+        }
+
+        let path_id = Regex::new(PATH_ID).unwrap();
+        let path_start = self.url.find("://").and_then(|i| self.url[i + 3..].find('/').map(|j| i + 3 + j));
+        if let Some(start) = path_start {
+            let end = self.url[start..].find(['?', '#']).map(|i| start + i).unwrap_or(self.url.len());
+            let mut offset = start + 1;
+            for (index, segment) in self.url[start + 1..end].split('/').enumerate() {
+                if path_id.is_match(segment) {
+                    points.push(point(Location::Path, &(index + 1).to_string(), offset..offset + segment.len()));
+                }
+                offset += segment.len() + 1;
+            }
+        }
+
+        match &self.body {
+            Some((Body::Form, body)) => {
+                let field = Regex::new(r"(?:^|&)([^=&]+)=([^&]*)").unwrap();
+                for caps in field.captures_iter(body) {
+                    points.push(point(Location::Form, &decode_value(&caps[1]), caps.get(2).unwrap().range()));
+                }
+            }
+            Some((Body::Json, body)) => {
+                if let Ok(value) = serde_json::from_str::<Value>(body) {
+                    json_leaves(&value, "", &mut points);
+                }
+            }
+            None => {}
+        }
+
+        if self.header_points {
+            for name in HEADERS {
+                points.push(point(Location::Header, name, 0..self.header_base(name).len()));
+            }
+        }
+
+        let cookie = Regex::new(r"(?:^|;\s*)([^=;\s]+)=([^;]*)").unwrap();
+        for (index, (name, value)) in self.headers.iter().enumerate() {
+            if name == "Cookie" {
+                for caps in cookie.captures_iter(value) {
+                    points.push(Point { header: index, ..point(Location::Cookie, &caps[1], caps.get(2).unwrap().range()) });
+                }
+            }
+        }
+        points
+    }
+}
+
+// 递归收集 JSON 中的字符串、数字和布尔值，名称使用 a.b.0 形式
+fn json_leaves(value: &Value, pointer: &str, points: &mut Vec<Point>) {
+    let escape = |key: &str| key.replace('~', "~0").replace('/', "~1");
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| json_leaves(v, &format!("{}/{}", pointer, escape(k)), points)),
+        Value::Array(items) => items.iter().enumerate().for_each(|(i, v)| json_leaves(v, &format!("{}/{}", pointer, i), points)),
+        Value::Null => {}
+        _ => {
+            let name = pointer.trim_start_matches('/').split('/').map(|k| k.replace("~1", "/").replace("~0", "~")).collect::<Vec<_>>().join(".");
+            points.push(Point { location: Location::Json, name, value: 0..0, header: 0, pointer: pointer.to_string() });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Location {
+    Query,
+    Path,
+    Form,
+    Json,
+    Header,
+    Cookie,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Location::Query => "query",
+            Location::Path => "path",
+            Location::Form => "form",
+            Location::Json => "json",
+            Location::Header => "header",
+            Location::Cookie => "cookie",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 单个注入点，路径段的名称为从 1 开始的段序号
+#[derive(Debug, Clone)]
+pub(crate) struct Point {
+    pub(crate) location: Location,
+    pub(crate) name: String,
+    // Query/Path 为 url 中的位置，Form 为请求体中的位置，Cookie 为第 header 个请求头值中的位置，
+    // Header 为该请求头原值中的位置
+    value: Range<usize>,
+    header: usize,
+    // Json 为 JSON Pointer
+    pointer: String,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.name)
+    }
+}
+
+impl Point {
+    /// 注入点的原始值（已解码）
+    pub(crate) fn original(&self, target: &Target) -> String {
+        match self.location {
+            Location::Query | Location::Path => decode_value(&target.url[self.value.clone()]),
+            Location::Form => target.body.as_ref().map(|(_, body)| decode_value(&body[self.value.clone()])).unwrap_or_default(),
+            Location::Header => target.header_base(&self.name),
+            Location::Cookie => decode_value(&target.headers[self.header].1[self.value.clone()]),
+            Location::Json => target.body.as_ref()
+                .and_then(|(_, body)| serde_json::from_str::<Value>(body).ok())
+                .and_then(|json| json.pointer(&self.pointer).map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                }))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 替换（append 为 false）或追加注入值后的请求；wire 为 URL 中的写法，
+    /// JSON 与请求头中使用解码后的值
+    pub(crate) fn inject(&self, target: &Target, wire: &str, append: bool) -> Target {
+        let splice = |s: &str, value: &str| {
+            let start = if append { self.value.end } else { self.value.start };
+            format!("{}{}{}", &s[..start], value, &s[self.value.end..])
+        };
+        let mut mutated = target.clone();
+        match self.location {
+            Location::Query | Location::Path => mutated.url = splice(&target.url, wire),
+            Location::Form => {
+                if let Some((_, body)) = mutated.body.as_mut() {
+                    *body = splice(body, wire);
+                }
+            }
+            Location::Cookie => mutated.headers[self.header].1 = splice(&target.headers[self.header].1, wire),
+            Location::Header => {
+                let value = decode_value(wire).replace(['\r', '\n'], "");
+                mutated.headers.push((self.name.clone(), splice(&target.header_base(&self.name), &value)));
+            }
+            Location::Json => {
+                if let Some((_, body)) = mutated.body.as_mut() {
+                    if let Ok(mut json) = serde_json::from_str::<Value>(body) {
+                        if let Some(slot) = json.pointer_mut(&self.pointer) {
+                            let original = match &*slot {
+                                Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            };
+                            let value = decode_value(wire);
+                            *slot = Value::String(if append { original + &value } else { value });
+                        }
+                        *body = json.to_string();
+                    }
+                }
+            }
+        }
+        mutated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(target: &Target) -> Vec<String> {
+        target.points().iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn query_and_path_points() {
+        let target = Target::get("http://t/api/users/42/orders/6f1c2b3a-0d4e-4f5a-9b8c-7d6e5f4a3b2c?id=1&name=x");
+        assert_eq!(names(&target), vec!["query: id", "query: name", "path: 3", "path: 5"]);
+        let points = target.points();
+        assert_eq!(points[1].inject(&target, "x%27", false).url, "http://t/api/users/42/orders/6f1c2b3a-0d4e-4f5a-9b8c-7d6e5f4a3b2c?id=1&name=x%27");
+        assert_eq!(points[2].inject(&target, "%20AND%201=1", true).url.split('?').next().unwrap(),
+            "http://t/api/users/42%20AND%201=1/orders/6f1c2b3a-0d4e-4f5a-9b8c-7d6e5f4a3b2c");
+    }
+
+    #[test]
+    fn form_and_json_bodies() {
+        let form = Form {
+            method: "POST".to_string(),
+            action: "http://t/login".to_string(),
+            enctype: String::new(),
+            fields: vec![("user".to_string(), "admin".to_string()), ("pass".to_string(), "a b".to_string())],
+        };
+        let target = Target::from_form(&form);
+        assert_eq!(target.to_string(), "POST http://t/login");
+        assert_eq!(names(&target), vec!["form: user", "form: pass"]);
+        assert_eq!(target.points()[1].original(&target), "a b");
+        let mutated = target.points()[0].inject(&target, "%27", true);
+        assert_eq!(mutated.body, Some((Body::Form, "user=admin%27&pass=a%20b".to_string())));
+
+        let json = Target { body: Some((Body::Json, r#"{"user":{"id":7,"tags":["a"]},"q":"x"}"#.to_string())), ..target.clone() };
+        assert_eq!(names(&json), vec!["json: q", "json: user.id", "json: user.tags.0"]);
+        assert_eq!(json.points()[1].original(&json), "7");
+        let mutated = json.points()[1].inject(&json, "%27%22", true);
+        assert_eq!(mutated.body, Some((Body::Json, r#"{"q":"x","user":{"id":"7'\"","tags":["a"]}}"#.to_string())));
+
+        let get = Target::from_form(&Form { method: "GET".to_string(), action: "http://t/s?a=1#top".to_string(), ..form });
+        assert_eq!(get.url, "http://t/s?a=1&user=admin&pass=a%20b");
+    }
+
+    #[test]
+    fn header_and_cookie_points() {
+        let mut target = Target::get("http://t/?id=1").with_headers();
+        target.headers.push(("Cookie".to_string(), "sid=abc; lang=zh".to_string()));
+        assert_eq!(
+            names(&target),
+            vec!["query: id", "header: User-Agent", "header: Referer", "header: X-Forwarded-For", "cookie: sid", "cookie: lang"]
+        );
+        // 未变异的请求不带这些请求头，只在注入对应请求头时发送
+        assert_eq!(target.headers.len(), 1);
+        let points = target.points();
+        let xff = points[3].inject(&target, "127.0.0.1%27%0d%0aX:%201", false);
+        assert_eq!(xff.headers[1], ("X-Forwarded-For".to_string(), "127.0.0.1'X: 1".to_string()));
+        let referer = points[2].inject(&target, "%27", true);
+        assert_eq!(referer.headers[1], ("Referer".to_string(), "http://t/?id=1'".to_string()));
+        let lang = points[5].inject(&target, "%27", true);
+        assert_eq!(lang.headers, vec![("Cookie".to_string(), "sid=abc; lang=zh%27".to_string())]);
+    }
+}
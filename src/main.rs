@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::fs;
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use rand::Rng;
 mod outprint;
 mod infoscan;
@@ -8,10 +8,16 @@ mod tofile;
 mod cmsck;
 mod craw;
 mod vulns;
+mod inject;
 mod port;
 mod subdomain;
 mod pocscan;
 mod icpscan;
+mod resource;
+mod fingerimport;
+mod nuclei;
+mod validate;
+mod oob;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -121,6 +127,106 @@ Author: akyo    Version: 0.0.5"#
                 .value_name("CONFIG_PATH")
                 .help("Specifies a custom config file path"),
         )
+        .arg(
+            Arg::new("config-dir")
+                .long("config-dir")
+                .value_name("DIR")
+                .help("Directory to load finger.json, pocs.yaml, payloads.yaml, config.toml and dictionaries from (also $REND_HOME)"),
+        )
+        .arg(
+            Arg::new("fingers")
+                .long("fingers")
+                .value_name("FILE")
+                .help("Overrides the fingerprint file (finger.json)"),
+        )
+        .arg(
+            Arg::new("pocs")
+                .long("pocs")
+                .value_name("FILE")
+                .help("Overrides the yaml pocs file (pocs.yaml)"),
+        )
+        .arg(
+            Arg::new("payloads")
+                .long("payloads")
+                .value_name("FILE")
+                .help("Overrides the parameter scan payloads file (payloads.yaml)"),
+        )
+        .arg(
+            Arg::new("paths")
+                .long("paths")
+                .value_name("FILE")
+                .help("Overrides the path dictionary (path.txt)"),
+        )
+        .arg(
+            Arg::new("subdict")
+                .long("subdict")
+                .value_name("FILE")
+                .help("Overrides the subdomain dictionary (subdomain.txt)"),
+        )
+        .arg(
+            Arg::new("all-pocs")
+                .long("all-pocs")
+                .action(ArgAction::SetTrue)
+                .help("Runs every yaml poc against every target instead of selecting by fingerprint"),
+        )
+        .arg(
+            Arg::new("severity")
+                .long("severity")
+                .value_name("LIST")
+                .help("Only runs yaml pocs with these severities, e.g. high,critical"),
+        )
+        .arg(
+            Arg::new("tags")
+                .long("tags")
+                .value_name("LIST")
+                .help("Only runs yaml pocs with any of these tags, e.g. oa,file-read"),
+        )
+        .arg(
+            Arg::new("oob")
+                .long("oob")
+                .action(ArgAction::SetTrue)
+                .help("Starts the built-in HTTP/DNS callback listener for blind SSRF/RCE detection"),
+        )
+        .arg(
+            Arg::new("oob-host")
+                .long("oob-host")
+                .value_name("HOST")
+                .help("Public IP or host that targets use to reach the callback listener (default: local ip)"),
+        )
+        .arg(
+            Arg::new("oob-domain")
+                .long("oob-domain")
+                .value_name("DOMAIN")
+                .help("Domain delegated to the callback listener, enables DNS callbacks"),
+        )
+        .arg(
+            Arg::new("import-fingers")
+                .long("import-fingers")
+                .value_name("PATH")
+                .action(ArgAction::Append)
+                .help("Imports an EHole/Wappalyzer/Goby fingerprint file or directory (repeatable)"),
+        )
+        .arg(
+            Arg::new("nuclei-templates")
+                .long("nuclei-templates")
+                .value_name("PATH")
+                .action(ArgAction::Append)
+                .help("Imports nuclei http templates from a file or directory as yaml pocs (repeatable)"),
+        )
+        .subcommand(
+            Command::new("poc")
+                .about("Yaml poc utilities")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("validate")
+                        .about("Checks poc (.yaml), payload (.yaml) and fingerprint (.json) files, defaults to the loaded pocs.yaml, payloads.yaml and finger.json")
+                        .arg(
+                            Arg::new("files")
+                                .value_name("FILE")
+                                .action(ArgAction::Append),
+                        ),
+                ),
+        )
         .arg_required_else_help(true)
         .get_matches();
 
@@ -132,6 +238,64 @@ Author: akyo    Version: 0.0.5"#
         }
     }
 
+    // 初始化资源加载器：命令行指定文件 > 配置目录 > 内置默认
+    let mut loader = resource::ResourceLoader::new(args.get_one::<String>("config-dir").map(|s| s.as_str()));
+    for (flag, res) in [
+        ("fingers", resource::Resource::Fingers),
+        ("pocs", resource::Resource::Pocs),
+        ("payloads", resource::Resource::Payloads),
+        ("paths", resource::Resource::Paths),
+        ("subdict", resource::Resource::Subdict),
+    ] {
+        if let Some(path) = args.get_one::<String>(flag) {
+            if fs::metadata(path).is_err() {
+                eprintln!("Error: Resource file '{}' does not exist.", path);
+                return Err("Resource file not found".into());
+            }
+            loader = loader.with_override(res, path);
+        }
+    }
+    if let Some(paths) = args.get_many::<String>("import-fingers") {
+        for path in paths {
+            if fs::metadata(path).is_err() {
+                eprintln!("Error: Fingerprint file '{}' does not exist.", path);
+                return Err("Fingerprint file not found".into());
+            }
+            outprint::Print::infoprint(format!("Load Import Fingers: {}", path).as_str());
+            loader = loader.with_fingerprint_import(path);
+        }
+    }
+    if let Some(paths) = args.get_many::<String>("nuclei-templates") {
+        for path in paths {
+            if fs::metadata(path).is_err() {
+                eprintln!("Error: Nuclei template path '{}' does not exist.", path);
+                return Err("Nuclei template path not found".into());
+            }
+            outprint::Print::infoprint(format!("Load Nuclei Templates: {}", path).as_str());
+            loader = loader.with_nuclei_templates(path);
+        }
+    }
+    resource::init(loader);
+
+    // rend poc validate [FILE...]，只涉及 POC、payload 与指纹，不输出其余资源的加载信息
+    if let Some(("poc", poc_args)) = args.subcommand() {
+        if let Some(("validate", validate_args)) = poc_args.subcommand() {
+            let files: Vec<String> = validate_args.get_many::<String>("files").map(|f| f.cloned().collect()).unwrap_or_default();
+            return validate::run(&files);
+        }
+    }
+
+    for (name, res) in [
+        ("Fingers", resource::Resource::Fingers),
+        ("Pocs", resource::Resource::Pocs),
+        ("Payloads", resource::Resource::Payloads),
+        ("Config", resource::Resource::Config),
+        ("Paths", resource::Resource::Paths),
+        ("Subdict", resource::Resource::Subdict),
+    ] {
+        outprint::Print::infoprint(format!("Load {}: {}", name, resource::source(res)).as_str());
+    }
+
     // 解析其他参数
     let mut arg = HashMap::new();
     if let Some(domain) = args.get_one::<String>("domain") {
@@ -142,6 +306,25 @@ Author: akyo    Version: 0.0.5"#
         outprint::Print::infoprint(format!("Load File: {}", file_path).as_str());
         arg.insert("file", file_path.clone());
     }
+    if args.get_flag("all-pocs") {
+        outprint::Print::infoprint("Load Pocs Mode: all");
+        arg.insert("all_pocs", "true".to_string());
+    }
+    if let Some(severity) = args.get_one::<String>("severity") {
+        arg.insert("poc_severity", severity.to_string());
+    }
+    if let Some(tags) = args.get_one::<String>("tags") {
+        arg.insert("poc_tags", tags.to_string());
+    }
+    if args.get_flag("oob") {
+        arg.insert("oob", "true".to_string());
+    }
+    if let Some(host) = args.get_one::<String>("oob-host") {
+        arg.insert("oob_host", host.to_string());
+    }
+    if let Some(domain) = args.get_one::<String>("oob-domain") {
+        arg.insert("oob_domain", domain.to_string());
+    }
     let threads = args.get_one::<String>("threads").unwrap();
     outprint::Print::infoprint(format!("Load Threads: {}", threads).as_str());
     let headers = args.get_one::<String>("headers").unwrap();
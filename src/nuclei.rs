@@ -0,0 +1,357 @@
+// nuclei http 模板转换为 rend yaml poc 结构，仅支持常用子集，不支持的特性逐条列出
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde_yaml::{Mapping, Value};
+use crate::pocscan::{is_class_tag, DslExpr, RawRequest};
+
+// 以下字段之外的内容视为不支持
+const TEMPLATE_KEYS: &[&str] = &["id", "info", "http", "requests"];
+const REQUEST_KEYS: &[&str] = &[
+    "method", "path", "raw", "headers", "body", "matchers", "matchers-condition", "extractors",
+    "redirects", "host-redirects", "max-redirects", "cookie-reuse", "stop-at-first-match", "max-size", "unsafe",
+];
+const MATCHER_KEYS: &[&str] = &[
+    "type", "part", "words", "regex", "status", "size", "binary", "dsl", "condition", "negative",
+    "case-insensitive", "name", "internal",
+];
+const EXTRACTOR_KEYS: &[&str] = &["type", "name", "part", "regex", "group", "json", "kval", "internal"];
+// 运行时由 PocSession 提供的内置变量
+const BUILTIN_VARS: &[&str] = &["BaseURL", "RootURL", "Hostname", "Host", "Port", "Scheme"];
+// nuclei 中含义相同但写法不同的漏洞类型标签
+const TAG_ALIASES: &[(&str, &str)] = &[
+    ("fileupload", "upload"),
+    ("file-upload", "upload"),
+    ("disclosure", "info-leak"),
+    ("unauthenticated", "unauth"),
+    ("default-credentials", "default-login"),
+    ("open-redirect", "redirect"),
+    ("path-traversal", "traversal"),
+];
+
+#[derive(Debug, Default)]
+pub struct NucleiImport {
+    // rend 格式的 poc，由 pocscan 反序列化
+    pub pocs: Vec<Value>,
+    // 模板标识 -> 不支持的原因
+    pub skipped: Vec<(String, Vec<String>)>,
+}
+
+/// 判断 yaml 是否为 nuclei 模板
+pub fn is_template(value: &Value) -> bool {
+    value.get("id").is_some() && value.get("info").is_some()
+}
+
+/// 导入文件或目录（递归读取 .yaml / .yml）
+pub fn import_path(path: &Path) -> Result<NucleiImport, Box<dyn Error + Send + Sync>> {
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    files.sort();
+    let mut imported = NucleiImport::default();
+    for file in files {
+        let value: Value = match serde_yaml::from_str(&fs::read_to_string(&file)?) {
+            Ok(value) => value,
+            Err(e) => {
+                imported.skipped.push((file.display().to_string(), vec![format!("invalid yaml: {}", e)]));
+                continue;
+            }
+        };
+        if !is_template(&value) {
+            continue;
+        }
+        match convert(&value) {
+            Ok(poc) => imported.pocs.push(poc),
+            Err(reasons) => imported.skipped.push((file.display().to_string(), reasons)),
+        }
+    }
+    Ok(imported)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<std::path::PathBuf>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_files(&entry?.path(), files)?;
+        }
+    } else if path.extension().map(|ext| ext == "yaml" || ext == "yml").unwrap_or(false) {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Sequence(items)) => items.iter().filter_map(|i| i.as_str().map(String::from)).collect(),
+        _ => vec![],
+    }
+}
+
+// nuclei 标签中的产品名与 rend 指纹名称并不对应，只保留漏洞类型标签，
+// 使导入的模板作为通用 POC 执行，避免未指定 --all-pocs 时被静默跳过
+fn class_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.to_lowercase()) {
+        let tag = TAG_ALIASES.iter().find(|(from, _)| *from == tag).map(|(_, to)| to.to_string()).unwrap_or(tag);
+        if is_class_tag(&tag) && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+// info.tags 为逗号分隔的字符串
+fn tag_list(value: Option<&Value>) -> Vec<String> {
+    string_list(value)
+        .iter()
+        .flat_map(|t| t.split(','))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn unknown_keys(map: &Mapping, allowed: &[&str], what: &str, reasons: &mut Vec<String>) {
+    for key in map.keys().filter_map(|k| k.as_str()) {
+        if !allowed.contains(&key) {
+            reasons.push(format!("{} field '{}'", what, key));
+        }
+    }
+}
+
+/// 将单个 nuclei 模板转换为 rend poc，失败时返回全部不支持的特性
+pub fn convert(template: &Value) -> Result<Value, Vec<String>> {
+    let mut reasons = Vec::new();
+    let Some(root) = template.as_mapping() else {
+        return Err(vec!["template is not a mapping".to_string()]);
+    };
+    unknown_keys(root, TEMPLATE_KEYS, "template", &mut reasons);
+
+    let id = template.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let info = template.get("info");
+    let name = info.and_then(|i| i.get("name")).and_then(|v| v.as_str()).unwrap_or(&id).to_string();
+
+    let blocks = template.get("http").or_else(|| template.get("requests")).and_then(|v| v.as_sequence());
+    let Some(blocks) = blocks else {
+        reasons.push("no http requests (only the http protocol is supported)".to_string());
+        return Err(reasons);
+    };
+
+    // 模板中所有提取器定义的变量，后续请求可引用
+    let mut known_vars: Vec<String> = BUILTIN_VARS.iter().map(|v| v.to_string()).collect();
+    for block in blocks {
+        for extractor in block.get("extractors").and_then(|e| e.as_sequence()).into_iter().flatten() {
+            if let Some(name) = extractor.get("name").and_then(|n| n.as_str()) {
+                known_vars.push(name.to_string());
+            }
+        }
+    }
+
+    let mut requests = Vec::new();
+    for block in blocks {
+        match block.as_mapping() {
+            Some(map) => requests.extend(convert_block(map, &known_vars, &mut reasons)),
+            None => reasons.push("http request is not a mapping".to_string()),
+        }
+    }
+
+    if !reasons.is_empty() {
+        return Err(reasons);
+    }
+    let mut poc = Mapping::new();
+    poc.insert("id".into(), id.into());
+    poc.insert("name".into(), name.into());
+    if let Some(severity) = info.and_then(|i| i.get("severity")).and_then(|v| v.as_str()) {
+        poc.insert("severity".into(), severity.into());
+    }
+    for key in ["description", "remediation"] {
+        if let Some(text) = info.and_then(|i| i.get(key)).and_then(|v| v.as_str()) {
+            poc.insert(key.into(), text.trim().into());
+        }
+    }
+    let references = string_list(info.and_then(|i| i.get("reference")));
+    poc.insert("references".into(), Value::Sequence(references.into_iter().map(Value::from).collect()));
+    let cve = tag_list(info.and_then(|i| i.get("classification")).and_then(|c| c.get("cve-id")));
+    poc.insert("cve".into(), Value::Sequence(cve.into_iter().map(|c| Value::from(c.to_uppercase())).collect()));
+    let tags = class_tags(tag_list(info.and_then(|i| i.get("tags"))));
+    poc.insert("tags".into(), Value::Sequence(tags.into_iter().map(Value::from).collect()));
+    poc.insert("requests".into(), Value::Sequence(requests));
+    Ok(Value::Mapping(poc))
+}
+
+fn convert_block(block: &Mapping, known_vars: &[String], reasons: &mut Vec<String>) -> Vec<Value> {
+    unknown_keys(block, REQUEST_KEYS, "request", reasons);
+
+    // 公共部分：matchers / extractors / 跳转设置
+    let mut common = Mapping::new();
+    let matchers: Vec<Value> = block
+        .get("matchers")
+        .and_then(|m| m.as_sequence())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| convert_matcher(m, reasons))
+        .collect();
+    common.insert("matchers".into(), Value::Sequence(matchers));
+    let extractors: Vec<Value> = block
+        .get("extractors")
+        .and_then(|e| e.as_sequence())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| convert_extractor(e, reasons))
+        .collect();
+    common.insert("extractors".into(), Value::Sequence(extractors));
+    if let Some(condition) = block.get("matchers-condition") {
+        common.insert("matchers-condition".into(), condition.clone());
+    }
+    // nuclei 默认不跟随跳转
+    let redirects = ["redirects", "host-redirects"]
+        .iter()
+        .any(|k| block.get(*k).and_then(|v| v.as_bool()).unwrap_or(false));
+    common.insert("redirects".into(), redirects.into());
+
+    let mut requests = Vec::new();
+    if let Some(raws) = block.get("raw").and_then(|r| r.as_sequence()) {
+        let unsafe_raw = block.get("unsafe").and_then(|v| v.as_bool()).unwrap_or(false);
+        for raw in raws {
+            let Some(raw) = raw.as_str() else { continue };
+            if RawRequest::parse(raw).is_none() {
+                reasons.push("malformed raw request".to_string());
+                continue;
+            }
+            check_placeholders(raw, known_vars, reasons);
+            let mut request = common.clone();
+            request.insert("raw".into(), raw.into());
+            request.insert("unsafe".into(), unsafe_raw.into());
+            requests.push(Value::Mapping(request));
+        }
+        return requests;
+    }
+
+    let method = block.get("method").and_then(|m| m.as_str()).unwrap_or("GET").to_uppercase();
+    let headers: Vec<(String, String)> = block
+        .get("headers")
+        .and_then(|h| h.as_mapping())
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| Some((k.as_str()?.to_string(), value_string(v)?)))
+        .collect();
+    for (_, v) in &headers {
+        check_placeholders(v, known_vars, reasons);
+    }
+    let body = block.get("body").and_then(value_string);
+    if let Some(body) = &body {
+        check_placeholders(body, known_vars, reasons);
+    }
+    for path in string_list(block.get("path")) {
+        let Some(relative) = strip_base(&path) else {
+            reasons.push(format!("path '{}' does not start with {{{{BaseURL}}}} or {{{{RootURL}}}}", path));
+            continue;
+        };
+        check_placeholders(&relative, known_vars, reasons);
+        let mut request = common.clone();
+        request.insert("method".into(), method.clone().into());
+        request.insert("path".into(), relative.into());
+        if !headers.is_empty() {
+            request.insert("headers".into(), header_map(headers.clone()));
+        }
+        if let Some(body) = &body {
+            request.insert("body".into(), body.clone().into());
+        }
+        requests.push(Value::Mapping(request));
+    }
+    if requests.is_empty() && !block.contains_key("path") {
+        reasons.push("request without path or raw".to_string());
+    }
+    requests
+}
+
+fn value_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn header_map(headers: Vec<(String, String)>) -> Value {
+    Value::Mapping(headers.into_iter().map(|(k, v)| (Value::from(k), Value::from(v))).collect())
+}
+
+// 请求路径相对于目标拼接，{{BaseURL}} / {{RootURL}} 前缀去掉
+fn strip_base(path: &str) -> Option<String> {
+    ["{{BaseURL}}", "{{RootURL}}"]
+        .iter()
+        .find_map(|base| path.strip_prefix(base))
+        .map(String::from)
+}
+
+// 只允许内置变量与提取器变量，辅助函数（base64()、randstr 等）不支持
+fn check_placeholders(text: &str, known_vars: &[String], reasons: &mut Vec<String>) {
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        if !known_vars.iter().any(|v| v == name) {
+            reasons.push(format!("placeholder {{{{{}}}}}", name));
+        }
+        rest = &after[end + 2..];
+    }
+}
+
+fn convert_matcher(matcher: &Value, reasons: &mut Vec<String>) -> Option<Value> {
+    let map = matcher.as_mapping()?;
+    unknown_keys(map, MATCHER_KEYS, "matcher", reasons);
+    let matcher_type = matcher.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    if !matches!(matcher_type, "word" | "regex" | "status" | "size" | "binary" | "dsl") {
+        reasons.push(format!("matcher type '{}'", matcher_type));
+        return None;
+    }
+    for expr in string_list(matcher.get("dsl")) {
+        if DslExpr::parse(&expr).is_none() {
+            reasons.push(format!("dsl expression '{}'", expr));
+        }
+    }
+    let mut converted = map.clone();
+    converted.remove("name");
+    converted.remove("internal");
+    if let Some(part) = matcher.get("part").and_then(|p| p.as_str()) {
+        let part = match part {
+            "body" => "body",
+            "header" | "all_headers" => "header",
+            "all" | "response" | "raw" => "all",
+            other => {
+                reasons.push(format!("matcher part '{}'", other));
+                return None;
+            }
+        };
+        converted.insert("part".into(), part.into());
+    }
+    Some(Value::Mapping(converted))
+}
+
+fn convert_extractor(extractor: &Value, reasons: &mut Vec<String>) -> Option<Value> {
+    let map = extractor.as_mapping()?;
+    unknown_keys(map, EXTRACTOR_KEYS, "extractor", reasons);
+    let extractor_type = extractor.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    if !matches!(extractor_type, "regex" | "json" | "kval") {
+        reasons.push(format!("extractor type '{}'", extractor_type));
+        return None;
+    }
+    for path in string_list(extractor.get("json")) {
+        let simple = path.trim_start_matches('.').split('.').all(|k| {
+            !k.is_empty() && k.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        });
+        if !simple {
+            reasons.push(format!("json extractor query '{}'", path));
+        }
+    }
+    // 未命名的提取器在 nuclei 中仅用于输出，这里直接忽略
+    extractor.get("name")?;
+    let mut converted = map.clone();
+    converted.remove("internal");
+    // nuclei 正则提取器默认取整个匹配，rend 默认取第 1 个捕获组
+    if extractor_type == "regex" && !converted.contains_key("group") {
+        converted.insert("group".into(), 0.into());
+    }
+    Some(Value::Mapping(converted))
+}
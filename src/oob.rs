@@ -0,0 +1,325 @@
+// 带外（OOB）回连监听：内置 HTTP 与 DNS 服务，每个 payload 生成唯一 token，
+// 收到回连后根据 token 定位到触发的 URL 与参数，用于检测无回显的 SSRF / RCE
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, UdpSocket as StdUdpSocket};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::OnceCell;
+use rand::Rng;
+use regex::Regex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::time::{sleep, timeout, Duration};
+use crate::infoscan::OtherSets;
+use crate::outprint::Print;
+use crate::tofile;
+
+const TOKEN_LEN: usize = 12;
+const TOKEN_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+// 注入回连 payload 的位置
+#[derive(Debug, Clone)]
+pub(crate) struct Probe {
+    pub kind: String,
+    pub url: String,
+    pub param: String,
+}
+
+// 一次回连记录
+#[derive(Debug, Clone)]
+pub(crate) struct Interaction {
+    pub protocol: &'static str,
+    pub remote: String,
+    pub probe: Probe,
+}
+
+impl std::fmt::Display for Interaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[OOB][{}][{}] {} | param: {} | from {}",
+            self.protocol,
+            self.probe.kind.to_uppercase(),
+            self.probe.url,
+            self.probe.param,
+            self.remote
+        )
+    }
+}
+
+pub(crate) struct OobListener {
+    public_host: String,
+    http_port: u16,
+    dns_port: Option<u16>,
+    domain: Option<String>,
+    wait: Duration,
+    // 命中结果追加写入的文件，与参数漏洞检测一致
+    output: Option<String>,
+    probes: Mutex<HashMap<String, Probe>>,
+    interactions: Mutex<Vec<Interaction>>,
+    reported: Mutex<HashSet<(String, &'static str)>>,
+}
+
+// 在 infomain 中根据配置启动，未启用时为空
+static OOB: OnceCell<Arc<OobListener>> = OnceCell::new();
+
+pub(crate) fn listener() -> Option<Arc<OobListener>> {
+    OOB.get().cloned()
+}
+
+/// 根据 otherset 启动监听，oob_public_host 为空时使用本机出口 IP
+pub async fn init(otherset: &OtherSets) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !otherset.oob_enabled || OOB.get().is_some() {
+        return Ok(());
+    }
+    let public_host = match otherset.oob_public_host.trim() {
+        "" => local_ip().ok_or("Unable to detect local ip, set oob_public_host")?,
+        host => host.to_string(),
+    };
+    let domain = Some(otherset.oob_domain.trim().trim_matches('.').to_lowercase()).filter(|d| !d.is_empty());
+    let listener = OobListener::bind(
+        &otherset.oob_listen,
+        otherset.oob_http_port,
+        otherset.oob_dns_port,
+        public_host,
+        domain,
+        Duration::from_secs(otherset.oob_wait),
+        Some("vulns.txt".to_string()),
+    )
+    .await?;
+    Print::infoprint(format!("OOB listener started: {}", listener.http_url("token")).as_str());
+    if let (Some(name), Some(port)) = (listener.dns_name("token"), listener.dns_port) {
+        Print::infoprint(format!("OOB dns listener started: {} (udp port {})", name, port).as_str());
+    }
+    let _ = OOB.set(listener);
+    Ok(())
+}
+
+// 通过 UDP connect 获取出口网卡地址，不会实际发送数据
+fn local_ip() -> Option<String> {
+    let socket = StdUdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+fn new_token() -> String {
+    let mut rng = rand::thread_rng();
+    (0..TOKEN_LEN).map(|_| TOKEN_CHARS[rng.gen_range(0..TOKEN_CHARS.len())] as char).collect()
+}
+
+impl OobListener {
+    /// 绑定 HTTP 与 DNS 端口（0 表示随机端口），未配置 domain 时不启动 DNS
+    pub(crate) async fn bind(
+        listen: &str,
+        http_port: u16,
+        dns_port: u16,
+        public_host: String,
+        domain: Option<String>,
+        wait: Duration,
+        output: Option<String>,
+    ) -> Result<Arc<Self>, Box<dyn Error + Send + Sync>> {
+        let http = TcpListener::bind((listen, http_port)).await?;
+        let dns = match &domain {
+            Some(_) => match UdpSocket::bind((listen, dns_port)).await {
+                Ok(socket) => Some(socket),
+                Err(e) => {
+                    Print::errprint(format!("OOB dns listener failed on port {}: {}", dns_port, e).as_str());
+                    None
+                }
+            },
+            None => None,
+        };
+        let listener = Arc::new(OobListener {
+            public_host,
+            http_port: http.local_addr()?.port(),
+            dns_port: dns.as_ref().and_then(|d| d.local_addr().ok()).map(|a| a.port()),
+            domain: domain.filter(|_| dns.is_some()),
+            wait,
+            output,
+            probes: Mutex::new(HashMap::new()),
+            interactions: Mutex::new(Vec::new()),
+            reported: Mutex::new(HashSet::new()),
+        });
+        tokio::spawn(serve_http(http, listener.clone()));
+        if let Some(dns) = dns {
+            tokio::spawn(serve_dns(dns, listener.clone()));
+        }
+        Ok(listener)
+    }
+
+    /// 登记一个注入位置，返回对应的唯一 token
+    pub(crate) fn register(&self, kind: &str, url: &str, param: &str) -> String {
+        let mut probes = self.probes.lock().unwrap();
+        let token = loop {
+            let token = new_token();
+            if !probes.contains_key(&token) {
+                break token;
+            }
+        };
+        probes.insert(token.clone(), Probe { kind: kind.to_string(), url: url.to_string(), param: param.to_string() });
+        token
+    }
+
+    pub(crate) fn http_url(&self, token: &str) -> String {
+        format!("http://{}:{}/{}", self.public_host, self.http_port, token)
+    }
+
+    // 需要配置解析到本机的 domain（NS 记录指向 public_host）
+    pub(crate) fn dns_name(&self, token: &str) -> Option<String> {
+        self.domain.as_ref().map(|d| format!("{}.{}", token, d))
+    }
+
+    /// 扫描请求发送完成后，等待延迟到达的回连
+    pub(crate) async fn wait(&self) {
+        sleep(self.wait).await;
+    }
+
+    pub(crate) fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    // 在请求内容中查找已登记的 token，同一 token 每种协议只报告一次
+    fn correlate(&self, text: &str, protocol: &'static str, remote: &str) {
+        let re = Regex::new(&format!("[a-z0-9]{{{}}}", TOKEN_LEN)).unwrap();
+        let text = text.to_lowercase();
+        for token in re.find_iter(&text).map(|m| m.as_str()) {
+            let Some(probe) = self.probes.lock().unwrap().get(token).cloned() else {
+                continue;
+            };
+            if !self.reported.lock().unwrap().insert((token.to_string(), protocol)) {
+                continue;
+            }
+            let interaction = Interaction { protocol, remote: remote.to_string(), probe };
+            let line = interaction.to_string();
+            Print::vulnprint(&line);
+            if let Some(output) = &self.output {
+                let _ = tofile::vuln_save_to_file(output, &[line]);
+            }
+            self.interactions.lock().unwrap().push(interaction);
+        }
+    }
+
+    // DNS 查询的应答地址，public_host 不是 IPv4 时返回空应答
+    fn answer_ip(&self) -> Option<Ipv4Addr> {
+        match self.public_host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => Some(ip),
+            _ => None,
+        }
+    }
+}
+
+async fn serve_http(listener: TcpListener, oob: Arc<OobListener>) {
+    while let Ok((mut socket, remote)) = listener.accept().await {
+        let oob = oob.clone();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 8192];
+            let n = match timeout(Duration::from_secs(5), socket.read(&mut buffer)).await {
+                Ok(Ok(n)) => n,
+                _ => return,
+            };
+            let request = String::from_utf8_lossy(&buffer[..n]);
+            // 只看请求行与请求头
+            let head = request.split("\r\n\r\n").next().unwrap_or_default();
+            oob.correlate(head, "HTTP", &remote.ip().to_string());
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+        });
+    }
+}
+
+async fn serve_dns(socket: UdpSocket, oob: Arc<OobListener>) {
+    let mut buffer = [0u8; 512];
+    while let Ok((n, remote)) = socket.recv_from(&mut buffer).await {
+        let Some((name, question_end)) = parse_dns_query(&buffer[..n]) else {
+            continue;
+        };
+        oob.correlate(&name, "DNS", &remote.ip().to_string());
+        let response = dns_response(&buffer[..question_end], oob.answer_ip());
+        let _ = socket.send_to(&response, remote).await;
+    }
+}
+
+// 解析查询报文中的第一个问题，返回域名与问题段结束位置
+fn parse_dns_query(packet: &[u8]) -> Option<(String, usize)> {
+    if packet.len() < 12 || packet[2] & 0x80 != 0 || u16::from_be_bytes([packet[4], packet[5]]) == 0 {
+        return None;
+    }
+    let mut labels = Vec::new();
+    let mut pos = 12;
+    loop {
+        let len = *packet.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        if len & 0xc0 != 0 {
+            return None;
+        }
+        labels.push(String::from_utf8_lossy(packet.get(pos..pos + len)?).into_owned());
+        pos += len;
+    }
+    // QTYPE 与 QCLASS
+    packet.get(pos..pos + 4)?;
+    Some((labels.join("."), pos + 4))
+}
+
+// 复制请求头与问题段，A 查询时附带一条指向 answer 的记录
+fn dns_response(query: &[u8], answer: Option<Ipv4Addr>) -> Vec<u8> {
+    let qtype = u16::from_be_bytes([query[query.len() - 4], query[query.len() - 3]]);
+    let answer = answer.filter(|_| qtype == 1);
+    let mut response = query.to_vec();
+    // QR=1 AA=1，保留 RD
+    response[2] = 0x84 | (query[2] & 0x01);
+    response[3] = 0x00;
+    response[4..6].copy_from_slice(&1u16.to_be_bytes());
+    response[6..8].copy_from_slice(&(answer.is_some() as u16).to_be_bytes());
+    response[8..12].fill(0);
+    if let Some(ip) = answer {
+        response.extend_from_slice(&[0xc0, 0x0c, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04]);
+        response.extend_from_slice(&ip.octets());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dns_query(name: &str) -> Vec<u8> {
+        let mut packet = vec![0x12, 0x34, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        for label in name.split('.') {
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label.as_bytes());
+        }
+        packet.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x01]);
+        packet
+    }
+
+    #[tokio::test]
+    async fn http_and_dns_callbacks_correlate_to_probe() {
+        let oob = OobListener::bind("127.0.0.1", 0, 0, "127.0.0.1".to_string(), Some("oob.test".to_string()), Duration::from_millis(100), None)
+            .await
+            .unwrap();
+        let ssrf = oob.register("ssrf", "http://target/?url=1", "url");
+        let rce = oob.register("rce", "http://target/?cmd=1", "cmd");
+        let _unused = oob.register("rce", "http://target/?id=1", "id");
+
+        reqwest::get(oob.http_url(&ssrf)).await.unwrap();
+        reqwest::get(oob.http_url(&ssrf)).await.unwrap();
+
+        // 解析器可能改变大小写
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let query = dns_query(&oob.dns_name(&rce).unwrap().to_uppercase());
+        client.send_to(&query, ("127.0.0.1", oob.dns_port.unwrap())).await.unwrap();
+        let mut response = [0u8; 512];
+        let n = timeout(Duration::from_secs(2), client.recv(&mut response)).await.unwrap().unwrap();
+        assert_eq!(&response[..2], &[0x12, 0x34]);
+        assert_eq!(&response[6..8], &[0, 1]);
+        assert_eq!(&response[n - 4..n], &[127, 0, 0, 1]);
+
+        oob.wait().await;
+        let hits = oob.interactions();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().any(|h| h.protocol == "HTTP" && h.probe.param == "url" && h.probe.kind == "ssrf"));
+        assert!(hits.iter().any(|h| h.protocol == "DNS" && h.probe.url == "http://target/?cmd=1"));
+    }
+}
@@ -18,11 +18,17 @@ impl Print{
     pub fn errprint(err:&str){
         println!("{}",format!("[{}] {}","Err",err).yellow());
     }
+    pub fn warnprint(data:&str){
+        println!("{}",format!("[WARN] {}",data).yellow());
+    }
     pub fn infoprint(data:&str){
         println!("{}",format!("[INFO] {}",data).blue());
     }
     pub fn vulnprint(url:&str){ println!("{}",format!("{}{}","[*]".red(),url.blue()).bold()) }
-    pub fn yamlvulnprint(name:&str,url:&str){ println!("{}",format!("{}[{}] {}","[*]".red(),name,url).blue().bold()) }
+    pub fn yamlvulnprint(severity:&str,name:&str,url:&str,ids:&str){
+        let ids = if ids.is_empty() { String::new() } else { format!(" | {}", ids) };
+        println!("{}",format!("{}[{}][{}] {}{}","[*]".red(),severity.to_uppercase(),name,url,ids).blue().bold())
+    }
     pub fn bannerprint(banner:&str){ println!("{}",banner.blue().bold()) }
     pub fn otherprint(banner:&str){ println!("{}",format!("{}Find Link: {}","[+]".purple(),banner).blue().bold()) }
     pub fn passprint(outstr:&str){ println!("{}",format!("{} {}","[SKIP]",outstr).yellow()) }
@@ -1,38 +1,442 @@
 // pocscan.rs
-use reqwest::{Client, header::{HeaderMap, HeaderValue, HeaderName}};
+// POC 的多个请求按顺序执行：没有提取器的请求互为备选路径，任一命中即报告；
+// 带提取器的请求是多步链路中的前置步骤，matchers 只应写在链路的最后一步，
+// 前置步骤命中会提前报告并结束，后续步骤不再执行
+use reqwest::{Client, Method, header::{HeaderMap, HeaderValue, HeaderName}};
+use regex::Regex;
 use serde::Deserialize;
-use std::{collections::HashMap, error::Error, time::Duration, sync::{Arc, Mutex}};
+use std::{borrow::Cow, collections::HashMap, error::Error, time::Duration, sync::{Arc, Mutex}};
+use once_cell::sync::OnceCell;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tokio::sync::Semaphore;
+use tokio::time::timeout;
 use crate::outprint::Print;
 use crate::tofile::yaml_vuln_save_to_file;
+use crate::resource::{self, Resource};
+use crate::nuclei;
+use crate::validate::{self, Format, Issue, Problem};
+use crate::infoscan::OtherSets;
+
+// 不跟随跳转的客户端，在 infomain 中根据命令行参数初始化，用于匹配 30x 响应
+static NO_REDIRECT_CLIENT: OnceCell<Client> = OnceCell::new();
+
+pub fn init_no_redirect_client(client: Client) {
+    let _ = NO_REDIRECT_CLIENT.set(client);
+}
+
+pub(crate) fn no_redirect_client() -> Option<&'static Client> {
+    NO_REDIRECT_CLIENT.get()
+}
+
+fn default_part() -> String {
+    "body".to_string()
+}
+
+fn default_condition() -> String {
+    "or".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
 
 // 匹配yaml中的实际结构
 #[derive(Debug, Deserialize, Clone)]
 struct Matcher {
+    // word / regex / status / size / binary
     #[serde(rename = "type")]
     matcher_type: String,
+    // body / header / all
+    #[serde(default = "default_part")]
     part: String,
+    #[serde(default)]
     words: Vec<String>,
+    #[serde(default)]
+    regex: Vec<String>,
+    #[serde(default)]
+    status: Vec<u16>,
+    #[serde(default)]
+    size: Vec<usize>,
+    // 十六进制字节串，如 "504b0304"
+    #[serde(default)]
+    binary: Vec<String>,
+    // 简化版 nuclei DSL 表达式，见 DslExpr
+    #[serde(default)]
+    dsl: Vec<String>,
+    // word 匹配时忽略大小写
+    #[serde(rename = "case-insensitive", default)]
+    case_insensitive: bool,
+    #[serde(default = "default_condition")]
     condition: String,
+    // 取反，用于"响应中不应出现"的判断
+    #[serde(default)]
+    negative: bool,
+}
+
+fn default_group() -> usize {
+    1
+}
+
+// 从响应中提取值写入变量，供后续请求以 {{name}} 引用
+#[derive(Debug, Deserialize, Clone)]
+struct Extractor {
+    // regex / json / header / cookie
+    #[serde(rename = "type")]
+    extractor_type: String,
+    name: String,
+    #[serde(default = "default_part")]
+    part: String,
+    #[serde(default)]
+    regex: Vec<String>,
+    // 正则捕获组，不存在时取整个匹配
+    #[serde(default = "default_group")]
+    group: usize,
+    // json 路径，如 "data.token"、"items.0.id"
+    #[serde(default)]
+    json: Vec<String>,
+    // header / cookie 名称，kval 类型两者都查找（nuclei 中 "-" 写作 "_"）
+    #[serde(default)]
+    kval: Vec<String>,
+}
+
+impl Extractor {
+    fn extract(&self, response: &ResponseData) -> Option<String> {
+        match self.extractor_type.as_str() {
+            "regex" => {
+                let text = response.part(&self.part)?;
+                self.regex.iter().filter_map(|r| Regex::new(r).ok()).find_map(|re| {
+                    let caps = re.captures(&text)?;
+                    caps.get(self.group).or_else(|| caps.get(0)).map(|m| m.as_str().to_string())
+                })
+            }
+            "json" => {
+                let value: serde_json::Value = serde_json::from_str(&response.body).ok()?;
+                self.json.iter().find_map(|path| json_lookup(&value, path))
+            }
+            "header" => self.kval.iter().find_map(|name| {
+                response.headers.get(name.as_str()).and_then(|v| v.to_str().ok()).map(String::from)
+            }),
+            "cookie" => {
+                let cookies = response.set_cookies();
+                self.kval.iter().find_map(|name| {
+                    cookies.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+                })
+            }
+            "kval" => {
+                let cookies = response.set_cookies();
+                self.kval.iter().find_map(|name| {
+                    response
+                        .headers
+                        .get(name.replace('_', "-").as_str())
+                        .and_then(|v| v.to_str().ok())
+                        .map(String::from)
+                        .or_else(|| cookies.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()))
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn json_lookup(value: &serde_json::Value, path: &str) -> Option<String> {
+    let mut current = value;
+    for key in path.trim_start_matches('.').split('.').filter(|k| !k.is_empty()) {
+        current = match key.parse::<usize>() {
+            Ok(index) if current.is_array() => current.get(index)?,
+            _ => current.get(key)?,
+        };
+    }
+    match current {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+// 单次 POC 执行期间共享的变量与 Cookie
+#[derive(Debug, Default)]
+struct PocSession {
+    vars: HashMap<String, String>,
+    cookies: Vec<(String, String)>,
+}
+
+impl PocSession {
+    // 内置变量与 nuclei 保持一致：BaseURL / RootURL / Hostname / Host / Port / Scheme
+    fn new(base_url: &str) -> Self {
+        let mut session = PocSession::default();
+        session.vars.insert("BaseURL".to_string(), base_url.to_string());
+        if let Ok(url) = reqwest::Url::parse(base_url) {
+            let host = url.host_str().unwrap_or_default().to_string();
+            let port = url.port_or_known_default().unwrap_or_default();
+            let hostname = match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.clone(),
+            };
+            session.vars.insert("RootURL".to_string(), format!("{}://{}", url.scheme(), hostname));
+            session.vars.insert("Hostname".to_string(), hostname);
+            session.vars.insert("Host".to_string(), host);
+            session.vars.insert("Port".to_string(), port.to_string());
+            session.vars.insert("Scheme".to_string(), url.scheme().to_string());
+        }
+        session
+    }
+
+    // 替换 {{name}} 形式的变量，未定义的变量保持原样（避免误伤 SSTI 等 payload）
+    fn render(&self, template: &str) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find("}}") {
+                Some(end) => {
+                    match self.vars.get(after[..end].trim()) {
+                        Some(value) => out.push_str(value),
+                        None => out.push_str(&rest[start..start + 2 + end + 2]),
+                    }
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn store_cookies(&mut self, response: &ResponseData) {
+        for (name, value) in response.set_cookies() {
+            match self.cookies.iter_mut().find(|(k, _)| *k == name) {
+                Some(cookie) => cookie.1 = value,
+                None => self.cookies.push((name, value)),
+            }
+        }
+    }
+
+    fn cookie_header(&self) -> Option<String> {
+        if self.cookies.is_empty() {
+            return None;
+        }
+        Some(self.cookies.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("; "))
+    }
+
+    // 执行提取器，并返回是否全部提取成功
+    fn extract(&mut self, request: &Request, response: &ResponseData) -> bool {
+        self.store_cookies(response);
+        let mut complete = true;
+        for extractor in &request.extractors {
+            match extractor.extract(response) {
+                Some(value) => {
+                    self.vars.insert(extractor.name.clone(), value);
+                }
+                None => complete = false,
+            }
+        }
+        complete
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 struct Request {
+    #[serde(default = "default_method")]
     method: String,
+    #[serde(default)]
     path: String,
+    // 完整的原始 HTTP 请求（请求行、请求头、请求体），设置后忽略 method/path/headers/body
+    #[serde(default)]
+    raw: Option<String>,
+    // raw 请求不经 reqwest 处理，直接通过 socket 原样发送，用于 /..;/、%2e 等会被规范化的路径
+    #[serde(rename = "unsafe", default)]
+    unsafe_raw: bool,
+    // 仅用于登录、获取 token 等前置步骤时可以不写 matchers
+    #[serde(default)]
     matchers: Vec<Matcher>,
     #[serde(default)]
+    extractors: Vec<Extractor>,
+    // 多个 matcher 之间的关系，默认任意一个命中即可
+    #[serde(rename = "matchers-condition", default = "default_condition")]
+    matchers_condition: String,
+    // 是否跟随跳转，需要匹配 30x 状态码或 Location 时设为 false
+    #[serde(default = "default_true")]
+    redirects: bool,
+    #[serde(default)]
     body: Option<String>,
     #[serde(default)]
     headers: Option<HashMap<String, String>>,
 }
 
+impl Request {
+    fn matches(&self, response: &ResponseData) -> bool {
+        matchers_hit(&self.matchers, &self.matchers_condition, response)
+    }
+}
+
+fn matchers_hit(matchers: &[Matcher], condition: &str, response: &ResponseData) -> bool {
+    if matchers.is_empty() {
+        return false;
+    }
+    match condition {
+        "and" => matchers.iter().all(|m| check_vulnerability(response, m)),
+        _ => matchers.iter().any(|m| check_vulnerability(response, m)),
+    }
+}
+
+fn default_input_type() -> String {
+    "text".to_string()
+}
+
+fn default_read_size() -> usize {
+    1024
+}
+
+// network 请求中依次发送的数据
+#[derive(Debug, Deserialize, Clone)]
+struct NetworkInput {
+    data: String,
+    // text：支持 \x00、\r\n 等转义 / hex：十六进制字节串
+    #[serde(rename = "type", default = "default_input_type")]
+    input_type: String,
+    // 发送后立即读取的字节数，读到的内容拼接到响应中
+    #[serde(default)]
+    read: usize,
+}
+
+impl NetworkInput {
+    fn bytes(&self, session: &PocSession) -> Option<Vec<u8>> {
+        let data = session.render(&self.data);
+        match self.input_type.as_str() {
+            "hex" => decode_hex(&data),
+            "text" => Some(unescape(&data)),
+            _ => None,
+        }
+    }
+}
+
+// 常见服务端口，未指定 ports 的 network 请求不对其发送探测数据
+const SERVICE_PORTS: &[u16] = &[21, 22, 23, 53, 153, 445, 1433, 1521, 3306, 5432, 6379, 9200, 27017];
+
+// TCP 请求：连接 {{Host}}:{{Port}}，发送 inputs 后读取响应，使用 word / regex / binary / size 匹配
+#[derive(Debug, Deserialize, Clone)]
+struct NetworkRequest {
+    inputs: Vec<NetworkInput>,
+    // 只对这些端口执行，为空时对 SERVICE_PORTS 以外的所有开放端口执行
+    #[serde(default)]
+    ports: Vec<u16>,
+    // 发送完成后最多读取的字节数
+    #[serde(rename = "read-size", default = "default_read_size")]
+    read_size: usize,
+    #[serde(default)]
+    matchers: Vec<Matcher>,
+    #[serde(rename = "matchers-condition", default = "default_condition")]
+    matchers_condition: String,
+}
+
+impl NetworkRequest {
+    fn applies_to_port(&self, port: u16) -> bool {
+        if self.ports.is_empty() {
+            !SERVICE_PORTS.contains(&port)
+        } else {
+            self.ports.contains(&port)
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct Poc {
-    #[allow(dead_code)]
     id: String,
     name: String,
+    // 产品标签，与目标命中的指纹名称匹配（忽略大小写、包含即可）
+    #[serde(default)]
+    tags: Vec<String>,
+    // 必须命中的指纹名称（忽略大小写、完全一致）
+    #[serde(default)]
+    requires: Vec<String>,
+    // 漏洞等级：info / low / medium / high / critical
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    cve: Vec<String>,
+    #[serde(default)]
+    cnvd: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    remediation: Option<String>,
+    #[serde(default, alias = "reference")]
+    references: Vec<String>,
+    #[serde(default)]
     requests: Vec<Request>,
+    // TCP 请求，对端口扫描发现的开放端口执行，与 requests 二选一
+    #[serde(default)]
+    network: Vec<NetworkRequest>,
+    // 样例响应由测试回放，这里只记录数量，用于校验时提示缺少样例
+    #[serde(default)]
+    samples: Vec<serde::de::IgnoredAny>,
+}
+
+// 漏洞类型标签，仅用于 --tags 筛选，不参与指纹匹配
+const CLASS_TAGS: &[&str] = &[
+    "sqli", "rce", "xss", "ssrf", "xxe", "lfi", "file-read", "info-leak", "unauth", "auth-bypass",
+    "upload", "backup", "cve", "cnvd", "exposure", "misconfig", "default-login", "redirect", "traversal",
+];
+
+pub(crate) fn is_class_tag(tag: &str) -> bool {
+    CLASS_TAGS.contains(&tag)
+        || ["cve", "cnvd"].iter().any(|p| tag.strip_prefix(p).map(|y| !y.is_empty() && y.chars().all(|c| c.is_ascii_digit())).unwrap_or(false))
+}
+
+impl Poc {
+    // 根据目标命中的指纹判断是否执行该 POC，未设置 tags/requires 的通用 POC 始终执行
+    fn applies_to(&self, products: &[String]) -> bool {
+        let products: Vec<String> = products.iter().map(|p| p.to_lowercase()).collect();
+        if !self.requires.is_empty() {
+            return self.requires.iter().any(|r| products.contains(&r.to_lowercase()));
+        }
+        let product_tags: Vec<String> = self.tags.iter().map(|t| t.to_lowercase()).filter(|t| !is_class_tag(t)).collect();
+        if product_tags.is_empty() {
+            return true;
+        }
+        product_tags.iter().any(|t| products.iter().any(|p| p.contains(t)))
+    }
+
+    fn severity(&self) -> String {
+        self.severity.as_deref().unwrap_or("unknown").to_lowercase()
+    }
+
+    // --severity / --tags 筛选，均为空时不过滤；未标注等级的 POC 在按等级筛选时排除
+    fn selected(&self, severities: &[String], tags: &[String]) -> bool {
+        let severity = self.severity();
+        let severity_ok = severities.is_empty() || severities.iter().any(|s| s.eq_ignore_ascii_case(&severity));
+        let tags_ok = tags.is_empty() || self.tags.iter().any(|t| tags.iter().any(|f| f.eq_ignore_ascii_case(t)));
+        severity_ok && tags_ok
+    }
+
+    fn ids(&self) -> String {
+        self.cve.iter().chain(self.cnvd.iter()).cloned().collect::<Vec<_>>().join(", ")
+    }
+
+    // 文件输出：编号、描述、修复建议与参考链接
+    fn details(&self) -> String {
+        let ids = Some(self.ids()).filter(|i| !i.is_empty());
+        let references = Some(self.references.join(", ")).filter(|r| !r.is_empty());
+        [
+            ("ID", &ids),
+            ("Description", &self.description),
+            ("Remediation", &self.remediation),
+            ("References", &references),
+        ]
+        .iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| format!("{} -> {}", name, v)))
+        .collect::<Vec<_>>()
+        .join(" | ")
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -43,44 +447,188 @@ pub struct Pocs {
 // 定义一个结构来存储检测到的漏洞信息
 #[derive(Debug, Clone)]
 struct VulnInfo {
+    severity: String,
     name: String,
     url: String,
+    details: String,
 }
 
 impl Pocs {
-    pub fn from_yaml(content: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        Ok(serde_yaml::from_str(content)?)
+    // 逐条校验加载，跳过有错误或 ID 重复的 POC
+    fn load(content: &str) -> (Self, Vec<Issue>) {
+        let (pocs, issues) = validate::load_entries(content, Format::Yaml, "pocs", |p: &Poc| p.id.clone(), Poc::lint, true);
+        (Pocs { pocs }, issues)
+    }
+
+    // 按等级与标签筛选，返回剩余数量
+    fn filter(&mut self, severities: &[String], tags: &[String]) -> usize {
+        self.pocs.retain(|poc| poc.selected(severities, tags));
+        self.pocs.len()
+    }
+
+    // 导入 nuclei 模板，返回成功转换的数量；不支持的模板逐个输出原因
+    fn import_nuclei(&mut self, path: &std::path::Path) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let imported = nuclei::import_path(path)?;
+        for (template, reasons) in &imported.skipped {
+            Print::passprint(format!("nuclei template {} unsupported: {}", template, reasons.join("; ")).as_str());
+        }
+        let mut count = 0;
+        for value in imported.pocs {
+            match serde_yaml::from_value::<Poc>(value) {
+                Ok(poc) => {
+                    self.pocs.push(poc);
+                    count += 1;
+                }
+                Err(e) => Print::errprint(format!("Failed to load nuclei template from {}: {}", path.display(), e).as_str()),
+            }
+        }
+        Ok(count)
     }
 }
 
 struct ResponseData {
+    url: String,
+    status: u16,
     headers: HeaderMap,
     body: String,
+    raw: Vec<u8>,
 }
 
-async fn send_request(client: &Client, base_url: &str, request: &Request) -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
-    let url = format!("{}{}", base_url, request.path);
-    let mut headers = HeaderMap::new();
+impl ResponseData {
+    // 响应头名称还原为常见的首字母大写形式，如 "Set-Cookie: ..."
+    fn header_text(&self) -> String {
+        self.headers
+            .iter()
+            .map(|(k, v)| format!("{}: {}", title_case(k.as_str()), String::from_utf8_lossy(v.as_bytes())))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn set_cookies(&self) -> Vec<(String, String)> {
+        self.headers
+            .get_all("set-cookie")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| v.split(';').next()?.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect()
+    }
 
-    if let Some(header_map) = &request.headers {
-        for (k, v) in header_map {
-            headers.insert(
-                HeaderName::from_bytes(k.as_bytes())?,
-                HeaderValue::from_str(v)?,
-            );
+    fn part(&self, part: &str) -> Option<Cow<'_, str>> {
+        match part {
+            "body" => Some(Cow::Borrowed(&self.body)),
+            "header" => Some(Cow::Owned(self.header_text())),
+            "all" | "response" => Some(Cow::Owned(format!("{}\n\n{}", self.header_text(), self.body))),
+            _ => None,
         }
     }
+}
+
+fn title_case(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// 原始 HTTP 请求解析结果，Host / Content-Length 由客户端生成
+pub(crate) struct RawRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: String,
+}
+
+impl RawRequest {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim_start().replace("\r\n", "\n");
+        let (head, body) = raw.split_once("\n\n").unwrap_or((raw.as_str(), ""));
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_uppercase();
+        let path = request_line.next()?.to_string();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .filter(|(k, _)| !k.eq_ignore_ascii_case("host") && !k.eq_ignore_ascii_case("content-length"))
+            .collect();
+        Some(RawRequest { method, path, headers, body: body.trim_end_matches('\n').to_string() })
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+async fn send_request(client: &Client, base_url: &str, request: &Request, session: &PocSession) -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
+    let (method, path, request_headers, body) = match &request.raw {
+        Some(raw) => {
+            let raw = session.render(raw);
+            if request.unsafe_raw {
+                return send_unsafe(base_url, &raw, session.cookie_header()).await;
+            }
+            let parsed = RawRequest::parse(&raw).ok_or("Malformed raw request")?;
+            let body = Some(parsed.body).filter(|b| !b.is_empty());
+            (parsed.method, parsed.path, parsed.headers, body)
+        }
+        None => (
+            request.method.to_uppercase(),
+            session.render(&request.path),
+            request.headers.iter().flatten().map(|(k, v)| (k.clone(), session.render(v))).collect(),
+            request.body.as_deref().map(|b| session.render(b)),
+        ),
+    };
+    let url = if path.starts_with("http://") || path.starts_with("https://") {
+        path
+    } else {
+        format!("{}{}", base_url, path)
+    };
+    let mut headers = HeaderMap::new();
+
+    // 先带上会话 Cookie，请求中显式指定的 Cookie 头优先
+    if let Some(cookie) = session.cookie_header() {
+        headers.insert(HeaderName::from_static("cookie"), HeaderValue::from_str(&cookie)?);
+    }
+
+    for (k, v) in &request_headers {
+        headers.insert(
+            HeaderName::from_bytes(k.as_bytes())?,
+            HeaderValue::from_str(v)?,
+        );
+    }
 
     // 设置默认Content-Type如果是POST请求但没有指定
-    if request.method.to_lowercase() == "post" && !headers.contains_key("content-type") {
+    if method == "POST" && !headers.contains_key("content-type") {
         headers.insert(
             HeaderName::from_static("content-type"),
             HeaderValue::from_static("application/x-www-form-urlencoded"),
         );
     }
 
-    // 判断是否只需要检查header
-    let only_check_headers = request.matchers.iter().all(|m| m.part == "header");
+    // 判断是否只需要检查header（仅 GET 请求可以改用 HEAD）
+    let only_check_headers = method == "GET"
+        && !request.matchers.is_empty()
+        && request.matchers.iter().all(|m| m.part == "header")
+        && request.extractors.iter().all(|e| matches!(e.extractor_type.as_str(), "header" | "cookie"));
+
+    // 不跟随跳转时使用独立客户端，未初始化则退回默认客户端
+    let client = match (request.redirects, NO_REDIRECT_CLIENT.get()) {
+        (false, Some(no_redirect)) => no_redirect,
+        _ => client,
+    };
 
     let response = if only_check_headers {
         // 如果只需检查header，使用HEAD请求
@@ -90,15 +638,11 @@ async fn send_request(client: &Client, base_url: &str, request: &Request) -> Res
             .send()
             .await?
     } else {
-        // 否则使用原来的请求方式
-        let request_builder = match request.method.to_lowercase().as_str() {
-            "get" => client.get(&url).headers(headers),
-            "post" => {
-                let body = request.body.clone().unwrap_or_default();
-                client.post(&url).headers(headers).body(body)
-            }
-            _ => client.get(&url).headers(headers),
-        };
+        // 支持任意请求方法，包括自定义方法
+        let mut request_builder = client.request(Method::from_bytes(method.as_bytes())?, &url).headers(headers);
+        if let Some(body) = body {
+            request_builder = request_builder.body(body);
+        }
 
         request_builder
             .timeout(Duration::from_secs(20))
@@ -106,119 +650,693 @@ async fn send_request(client: &Client, base_url: &str, request: &Request) -> Res
             .await?
     };
 
-    let status = response.status();
-    if !status.is_success() {  // 400+ 状态码直接返回
-        return Ok(ResponseData {
-            headers: HeaderMap::new(),
-            body: String::new(),
-        });
-    }
-
-    // 保存响应头
+    // 保留所有状态码的响应，由 matcher 自行判断
+    let status = response.status().as_u16();
     let headers = response.headers().clone();
 
     // 获取响应体 - 只有在非HEAD请求时才获取
-    let body = if only_check_headers {
-        String::new()  // HEAD请求不需要读取body
+    let raw = if only_check_headers {
+        Vec::new()  // HEAD请求不需要读取body
+    } else {
+        response.bytes().await?.to_vec()
+    };
+    let body = String::from_utf8_lossy(&raw).into_owned();
+
+    Ok(ResponseData { url, status, headers, body, raw })
+}
+
+// unsafe 请求的读取上限，避免目标返回超大响应
+const UNSAFE_MAX_RESPONSE: u64 = 10 * 1024 * 1024;
+
+/// 通过 TCP/TLS 原样发送 raw 请求，仅统一换行符，并补充 Connection: close 以便读取完整响应
+async fn send_unsafe(base_url: &str, raw: &str, cookie: Option<String>) -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
+    let url = reqwest::Url::parse(base_url)?;
+    let host = url.host_str().ok_or("Missing host")?.to_string();
+    let port = url.port_or_known_default().ok_or("Missing port")?;
+
+    let raw = raw.trim_start().replace("\r\n", "\n");
+    let (head, body) = raw.split_once("\n\n").unwrap_or((raw.as_str(), ""));
+    let mut lines: Vec<&str> = head.lines().collect();
+    let has_header = |lines: &[&str], name: &str| {
+        lines.iter().skip(1).any(|l| l.split_once(':').map(|(k, _)| k.trim().eq_ignore_ascii_case(name)).unwrap_or(false))
+    };
+    let cookie_line = cookie.map(|c| format!("Cookie: {}", c));
+    if let Some(cookie_line) = &cookie_line {
+        if !has_header(&lines, "cookie") {
+            lines.push(cookie_line);
+        }
+    }
+    if !has_header(&lines, "connection") {
+        lines.push("Connection: close");
+    }
+    let path = lines.first().and_then(|l| l.split_whitespace().nth(1)).unwrap_or("/").to_string();
+    let mut payload = lines.join("\r\n").into_bytes();
+    payload.extend_from_slice(b"\r\n\r\n");
+    payload.extend_from_slice(body.as_bytes());
+
+    let io_timeout = Duration::from_secs(20);
+    let stream = timeout(io_timeout, TcpStream::connect((host.as_str(), port))).await??;
+    let response = if url.scheme() == "https" {
+        let connector = tokio_native_tls::native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+        let stream = timeout(io_timeout, tokio_native_tls::TlsConnector::from(connector).connect(&host, stream)).await??;
+        exchange(stream, &payload, io_timeout).await?
+    } else {
+        exchange(stream, &payload, io_timeout).await?
+    };
+
+    let request_url = if path.starts_with("http://") || path.starts_with("https://") {
+        path
     } else {
-        response.text().await?
+        format!("{}{}", base_url.trim_end_matches('/'), path)
     };
+    parse_raw_response(request_url, &response).ok_or_else(|| "Malformed HTTP response".into())
+}
 
-    Ok(ResponseData { headers, body })
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, payload: &[u8], io_timeout: Duration) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    timeout(io_timeout, stream.write_all(payload)).await??;
+    let mut response = Vec::new();
+    // 服务端未关闭连接时以超时结束，已读取的内容仍然有效
+    let _ = timeout(io_timeout, (&mut stream).take(UNSAFE_MAX_RESPONSE).read_to_end(&mut response)).await;
+    Ok(response)
+}
+
+// 解析原始 HTTP 响应，支持 chunked 与 Content-Length
+fn parse_raw_response(url: String, data: &[u8]) -> Option<ResponseData> {
+    let split = data.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = String::from_utf8_lossy(&data[..split]);
+    let mut body = data[split + 4..].to_vec();
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse::<u16>().ok()?;
+    let mut headers = HeaderMap::new();
+    for (k, v) in lines.filter_map(|l| l.split_once(':')) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(k.trim().as_bytes()), HeaderValue::from_str(v.trim())) {
+            headers.append(name, value);
+        }
+    }
+    let chunked = headers
+        .get("transfer-encoding")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    if chunked {
+        body = dechunk(&body);
+    } else if let Some(length) = headers.get("content-length").and_then(|v| v.to_str().ok()?.trim().parse::<usize>().ok()) {
+        body.truncate(length);
+    }
+    Some(ResponseData { url, status, headers, body: String::from_utf8_lossy(&body).into_owned(), raw: body })
+}
+
+fn dechunk(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut rest = data;
+    while let Some(line_end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let size_line = String::from_utf8_lossy(&rest[..line_end]);
+        let Ok(size) = usize::from_str_radix(size_line.split(';').next().unwrap_or_default().trim(), 16) else { break };
+        let start = line_end + 2;
+        if size == 0 || start + size > rest.len() {
+            out.extend_from_slice(&rest[start.min(rest.len())..(start + size).min(rest.len())]);
+            break;
+        }
+        out.extend_from_slice(&rest[start..start + size]);
+        rest = &rest[(start + size + 2).min(rest.len())..];
+    }
+    out
+}
+
+// 文本输入中的转义：\xNN、\r、\n、\t、\0、\\，其余原样保留
+fn unescape(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('\\') {
+        out.extend_from_slice(&rest.as_bytes()[..pos]);
+        let escaped = &rest[pos + 1..];
+        let (bytes, consumed): (Vec<u8>, usize) = match escaped.chars().next() {
+            Some('x') => match escaped.get(1..3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                Some(b) => (vec![b], 3),
+                None => (b"\\".to_vec(), 0),
+            },
+            Some('r') => (b"\r".to_vec(), 1),
+            Some('n') => (b"\n".to_vec(), 1),
+            Some('t') => (b"\t".to_vec(), 1),
+            Some('0') => (vec![0], 1),
+            Some('\\') => (b"\\".to_vec(), 1),
+            _ => (b"\\".to_vec(), 0),
+        };
+        out.extend_from_slice(&bytes);
+        rest = &escaped[consumed..];
+    }
+    out.extend_from_slice(rest.as_bytes());
+    out
+}
+
+// network 请求的连接与首次读取超时，之后连续读取时以较短的空闲超时结束
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+const NETWORK_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+async fn send_network(address: &str, request: &NetworkRequest, session: &PocSession) -> Result<ResponseData, Box<dyn Error + Send + Sync>> {
+    let mut stream = timeout(NETWORK_TIMEOUT, TcpStream::connect(address)).await??;
+    let mut data = Vec::new();
+    for input in &request.inputs {
+        let payload = input.bytes(session).ok_or("Invalid network input")?;
+        timeout(NETWORK_TIMEOUT, stream.write_all(&payload)).await??;
+        if input.read > 0 {
+            let mut buffer = vec![0; input.read];
+            if let Ok(Ok(n)) = timeout(NETWORK_TIMEOUT, stream.read(&mut buffer)).await {
+                data.extend_from_slice(&buffer[..n]);
+            }
+        }
+    }
+    // 读取到 read-size 字节、连接关闭或空闲超时为止
+    let mut buffer = vec![0; request.read_size];
+    let mut filled = 0;
+    let mut wait = NETWORK_TIMEOUT;
+    while filled < buffer.len() {
+        match timeout(wait, stream.read(&mut buffer[filled..])).await {
+            Ok(Ok(n)) if n > 0 => {
+                filled += n;
+                wait = NETWORK_IDLE_TIMEOUT;
+            }
+            _ => break,
+        }
+    }
+    data.extend_from_slice(&buffer[..filled]);
+    Ok(ResponseData {
+        url: address.to_string(),
+        status: 0,
+        headers: HeaderMap::new(),
+        body: String::from_utf8_lossy(&data).into_owned(),
+        raw: data,
+    })
 }
 
 fn check_vulnerability(response: &ResponseData, matcher: &Matcher) -> bool {
-    if matcher.matcher_type != "word" {
-        return false;  // 只处理word类型的匹配器
-    }
-
-    match matcher.part.as_str() {
-        "body" => {
-            // 匹配响应体
-            let check_any = |text: &str| matcher.words.iter().any(|s| text.contains(s));
-            let check_all = |text: &str| matcher.words.iter().all(|s| text.contains(s));
-
-            match matcher.condition.as_str() {
-                "or" => check_any(&response.body),
-                "and" => check_all(&response.body),
-                _ => false,
-            }
-        },
-        "header" => {
-            // 匹配响应头
-            let check_header_any = || {
-                for word in &matcher.words {
-                    // 检查是否有任何响应头的值包含指定的字符串
-                    for (_, value) in response.headers.iter() {
-                        if let Ok(value_str) = value.to_str() {
-                            if value_str.contains(word) {
-                                return true;
-                            }
-                        }
-                    }
-                    // 或者检查Content-Type头是否匹配
-                    if response.headers.get("content-type").map_or(false, |v| {
-                        v.to_str().map_or(false, |s| s.contains(word))
-                    }) {
-                        return true;
+    match match_condition(response, matcher) {
+        Some(matched) => matched != matcher.negative,
+        None => false,
+    }
+}
+
+// 不支持的类型或部位返回 None，取反时同样视为未命中
+fn match_condition(response: &ResponseData, matcher: &Matcher) -> Option<bool> {
+    let all = matcher.condition == "and";
+    let check = |hits: Vec<bool>| if all { hits.iter().all(|h| *h) } else { hits.iter().any(|h| *h) };
+
+    let matched = match matcher.matcher_type.as_str() {
+        "word" => {
+            let text = response.part(&matcher.part)?;
+            if matcher.words.is_empty() {
+                return None;
+            }
+            if matcher.case_insensitive {
+                let text = text.to_lowercase();
+                check(matcher.words.iter().map(|w| text.contains(&w.to_lowercase())).collect())
+            } else {
+                check(matcher.words.iter().map(|w| text.contains(w.as_str())).collect())
+            }
+        }
+        "regex" => {
+            let text = response.part(&matcher.part)?;
+            let regexes = matcher.regex.iter().map(|r| Regex::new(r).ok()).collect::<Option<Vec<_>>>()?;
+            if regexes.is_empty() {
+                return None;
+            }
+            check(regexes.iter().map(|re| re.is_match(&text)).collect())
+        }
+        "status" => !matcher.status.is_empty() && matcher.status.contains(&response.status),
+        "size" => !matcher.size.is_empty() && matcher.size.contains(&response.raw.len()),
+        "binary" => {
+            let data: Cow<[u8]> = match matcher.part.as_str() {
+                "body" => Cow::Borrowed(&response.raw),
+                part => match response.part(part)? {
+                    Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+                    Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+                },
+            };
+            let needles = matcher.binary.iter().map(|b| decode_hex(b)).collect::<Option<Vec<_>>>()?;
+            if needles.is_empty() {
+                return None;
+            }
+            check(needles.iter().map(|n| data.windows(n.len()).any(|w| w == n.as_slice())).collect())
+        }
+        "dsl" => {
+            let exprs = matcher.dsl.iter().map(|d| DslExpr::parse(d)).collect::<Option<Vec<_>>>()?;
+            if exprs.is_empty() {
+                return None;
+            }
+            check(exprs.iter().map(|e| e.eval(response)).collect())
+        }
+        _ => return None,
+    };
+    Some(matched)
+}
+
+// 在引号之外按分隔符切分
+fn split_outside_quotes<'a>(input: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let c = input[i..].chars().next().unwrap_or_default();
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if input[i..].starts_with(sep) => {
+                parts.push(&input[start..i]);
+                i += sep.len();
+                start = i;
+                continue;
+            }
+            None => {}
+        }
+        i += c.len_utf8();
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn string_literal(input: &str) -> Option<String> {
+    let input = input.trim();
+    let quote = input.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = input.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(inner.replace(&format!("\\{}", quote), &quote.to_string()).replace("\\\\", "\\"))
+}
+
+// DSL 中的响应部位，nuclei 的 all_headers / raw 分别对应 header / all
+fn dsl_part(input: &str) -> Option<(&'static str, bool)> {
+    let input = input.trim();
+    let (input, lower) = match input.strip_prefix("tolower(").and_then(|i| i.strip_suffix(')')) {
+        Some(inner) => (inner.trim(), true),
+        None => (input, false),
+    };
+    let part = match input {
+        "body" => "body",
+        "header" | "all_headers" => "header",
+        "response" | "raw" => "all",
+        _ => return None,
+    };
+    Some((part, lower))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+}
+
+impl CmpOp {
+    fn apply(&self, left: usize, right: usize) -> bool {
+        match self {
+            CmpOp::Eq => left == right,
+            CmpOp::Ne => left != right,
+            CmpOp::Ge => left >= right,
+            CmpOp::Le => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Lt => left < right,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum DslAtom {
+    // contains / contains_any / contains_all
+    Contains { part: &'static str, lower: bool, needles: Vec<String>, all: bool },
+    Regex { part: &'static str, pattern: Regex },
+    Status(CmpOp, usize),
+    Len { part: &'static str, op: CmpOp, value: usize },
+}
+
+/// 简化版 nuclei DSL：|| 连接的若干组 && 条件（不支持括号），原子表达式支持
+/// contains()/contains_any()/contains_all()/regex()/status_code/len()/content_length 及 ! 取反
+#[derive(Debug)]
+pub(crate) struct DslExpr {
+    any_of: Vec<Vec<(bool, DslAtom)>>,
+}
+
+impl DslExpr {
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let any_of = split_outside_quotes(input, "||")
+            .into_iter()
+            .map(|group| split_outside_quotes(group, "&&").into_iter().map(Self::parse_atom).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>()?;
+        Some(DslExpr { any_of })
+    }
+
+    fn parse_atom(input: &str) -> Option<(bool, DslAtom)> {
+        let input = input.trim();
+        if let Some(rest) = input.strip_prefix('!').filter(|r| !r.starts_with('=')) {
+            let (negated, atom) = Self::parse_atom(rest)?;
+            return Some((!negated, atom));
+        }
+        if let Some((name, args)) = input.strip_suffix(')').and_then(|i| i.split_once('(')).filter(|(n, _)| !n.contains(' ')) {
+            let args = split_outside_quotes(args, ",");
+            return match name {
+                "contains" | "contains_any" | "contains_all" if args.len() >= 2 => {
+                    let (part, lower) = dsl_part(args[0])?;
+                    let needles = args[1..].iter().map(|a| string_literal(a)).collect::<Option<Vec<_>>>()?;
+                    if name == "contains" && needles.len() != 1 {
+                        return None;
                     }
+                    Some((false, DslAtom::Contains { part, lower, needles, all: name == "contains_all" }))
+                }
+                "regex" if args.len() == 2 => {
+                    let (part, _) = dsl_part(args[1])?;
+                    let pattern = Regex::new(&string_literal(args[0])?).ok()?;
+                    Some((false, DslAtom::Regex { part, pattern }))
                 }
-                false
+                _ => None,
             };
+        }
+        for (token, op) in [("==", CmpOp::Eq), ("!=", CmpOp::Ne), (">=", CmpOp::Ge), ("<=", CmpOp::Le), (">", CmpOp::Gt), ("<", CmpOp::Lt)] {
+            if let Some((left, right)) = input.split_once(token) {
+                let value = right.trim().parse::<usize>().ok()?;
+                let left = left.trim();
+                return match left {
+                    "status_code" => Some((false, DslAtom::Status(op, value))),
+                    "content_length" => Some((false, DslAtom::Len { part: "body", op, value })),
+                    _ => {
+                        let inner = left.strip_prefix("len(")?.strip_suffix(')')?;
+                        let (part, _) = dsl_part(inner)?;
+                        Some((false, DslAtom::Len { part, op, value }))
+                    }
+                };
+            }
+        }
+        None
+    }
 
-            let check_header_all = || {
-                matcher.words.iter().all(|word| {
-                    response.headers.iter().any(|(_, value)| {
-                        value.to_str().map_or(false, |s| s.contains(word))
-                    }) ||
-                        response.headers.get("content-type").map_or(false, |v| {
-                            v.to_str().map_or(false, |s| s.contains(word))
-                        })
-                })
-            };
+    fn eval(&self, response: &ResponseData) -> bool {
+        self.any_of.iter().any(|group| {
+            group.iter().all(|(negated, atom)| Self::eval_atom(atom, response) != *negated)
+        })
+    }
 
-            match matcher.condition.as_str() {
-                "or" => check_header_any(),
-                "and" => check_header_all(),
-                _ => false,
+    fn eval_atom(atom: &DslAtom, response: &ResponseData) -> bool {
+        match atom {
+            DslAtom::Contains { part, lower, needles, all } => {
+                let Some(text) = response.part(part) else { return false };
+                let text = if *lower { Cow::Owned(text.to_lowercase()) } else { text };
+                let mut hits = needles.iter().map(|n| text.contains(n.as_str()));
+                if *all { hits.all(|h| h) } else { hits.any(|h| h) }
             }
-        },
-        _ => false, // 不支持的部分
+            DslAtom::Regex { part, pattern } => response.part(part).map(|t| pattern.is_match(&t)).unwrap_or(false),
+            DslAtom::Status(op, value) => op.apply(response.status as usize, *value),
+            DslAtom::Len { part, op, value } => {
+                let len = if *part == "body" { response.raw.len() } else { response.part(part).map(|t| t.len()).unwrap_or(0) };
+                op.apply(len, *value)
+            }
+        }
     }
 }
 
 async fn check_poc(client: Client, base_url: String, poc: Poc, vuln_results: Arc<Mutex<Vec<VulnInfo>>>) {
+    // 同一个 POC 的多个请求按顺序执行，共享变量与 Cookie
+    let mut session = PocSession::new(&base_url);
     for request in &poc.requests {
-        match send_request(&client, &base_url, request).await {
+        match send_request(&client, &base_url, request, &session).await {
             Ok(response) => {
-                // 检查所有匹配器
-                for matcher in &request.matchers {
-                    if check_vulnerability(&response, matcher) {
-                        let name = poc.name.clone();
-                        let url = format!("{}{}", base_url, request.path);
-
-                        // 将结果存入共享的漏洞列表中
-                        {
-                            let mut results = vuln_results.lock().unwrap();
-                            results.push(VulnInfo { name: name.clone(), url: url.clone() });
-                        }
-
-                        // 仅打印结果，不写入文件
-                        Print::yamlvulnprint(&name, &url);
-                        break;  // 匹配到一个就跳出当前request的检查
-                    }
+                // 按 matchers-condition 检查所有匹配器
+                if request.matches(&response) {
+                    let vuln = VulnInfo {
+                        severity: poc.severity(),
+                        name: poc.name.clone(),
+                        url: response.url.clone(),
+                        details: poc.details(),
+                    };
+
+                    // 仅打印结果，不写入文件
+                    Print::yamlvulnprint(&vuln.severity, &vuln.name, &vuln.url, &poc.ids());
+
+                    // 将结果存入共享的漏洞列表中
+                    vuln_results.lock().unwrap().push(vuln);
+                    // 每个 POC 只报告一次，多路径模板首个命中即结束
+                    break;
+                }
+                // 前置步骤未能提取到变量时，后续请求没有意义
+                if !session.extract(request, &response) {
+                    break;
                 }
             }
             Err(_) => {
-                // 请求失败，静默处理
+                // 请求失败，静默处理；前置步骤失败时后续请求会带着未替换的变量发出，直接结束
+                if !request.extractors.is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+const SEVERITIES: &[&str] = &["info", "low", "medium", "high", "critical"];
+const PARTS: &[&str] = &["body", "header", "all", "response"];
+
+fn lint_condition(condition: &str, field: &str) -> Option<Problem> {
+    (condition != "and" && condition != "or")
+        .then(|| Problem::error(format!("invalid {} '{}', expected and/or", field, condition)).at(condition))
+}
+
+pub(crate) fn lint_regexes(regexes: &[String], problems: &mut Vec<Problem>) {
+    for pattern in regexes {
+        if let Err(e) = Regex::new(pattern) {
+            let reason = e.to_string().lines().last().unwrap_or_default().to_string();
+            problems.push(Problem::error(format!("invalid regex '{}': {}", pattern, reason)).at(pattern));
+        }
+    }
+}
+
+impl Matcher {
+    fn lint(&self, problems: &mut Vec<Problem>) {
+        let empty = match self.matcher_type.as_str() {
+            "word" => self.words.is_empty(),
+            "regex" => {
+                lint_regexes(&self.regex, problems);
+                self.regex.is_empty()
+            }
+            "status" => self.status.is_empty(),
+            "size" => self.size.is_empty(),
+            "binary" => {
+                for hex in self.binary.iter().filter(|b| decode_hex(b).is_none()) {
+                    problems.push(Problem::error(format!("invalid hex in binary matcher '{}'", hex)).at(hex));
+                }
+                self.binary.is_empty()
+            }
+            "dsl" => {
+                for expr in self.dsl.iter().filter(|d| DslExpr::parse(d).is_none()) {
+                    problems.push(Problem::error(format!("unsupported dsl expression '{}'", expr)).at(expr));
+                }
+                self.dsl.is_empty()
+            }
+            other => {
+                problems.push(Problem::error(format!("unknown matcher type '{}'", other)).at(other));
+                return;
+            }
+        };
+        if empty {
+            let list = self.list_name();
+            problems.push(Problem::error(format!("empty {} list in {} matcher", list, self.matcher_type)).at(&format!("{}:", list)));
+        }
+        if !PARTS.contains(&self.part.as_str()) {
+            problems.push(Problem::error(format!("unknown matcher part '{}'", self.part)).at(&self.part));
+        }
+        problems.extend(lint_condition(&self.condition, "condition"));
+    }
+
+    fn list_name(&self) -> &str {
+        match self.matcher_type.as_str() {
+            "word" => "words",
+            other => other,
+        }
+    }
+}
+
+impl Extractor {
+    fn lint(&self, problems: &mut Vec<Problem>) {
+        if self.name.trim().is_empty() {
+            problems.push(Problem::error("extractor without name"));
+        }
+        let empty = match self.extractor_type.as_str() {
+            "regex" => {
+                lint_regexes(&self.regex, problems);
+                self.regex.is_empty()
             }
+            "json" => self.json.is_empty(),
+            "header" | "cookie" | "kval" => self.kval.is_empty(),
+            other => {
+                problems.push(Problem::error(format!("unknown extractor type '{}'", other)).at(other));
+                return;
+            }
+        };
+        if empty {
+            problems.push(Problem::error(format!("empty {} extractor '{}'", self.extractor_type, self.name)).at(&self.name));
+        }
+    }
+}
+
+impl Request {
+    fn lint(&self, problems: &mut Vec<Problem>) {
+        match &self.raw {
+            Some(raw) => {
+                if RawRequest::parse(raw).is_none() {
+                    problems.push(Problem::error("invalid raw request").at("raw:"));
+                }
+            }
+            None => {
+                if Method::from_bytes(self.method.as_bytes()).is_err() {
+                    problems.push(Problem::error(format!("invalid method '{}'", self.method)).at(&self.method));
+                }
+                if self.unsafe_raw {
+                    problems.push(Problem::warning("'unsafe' has no effect without 'raw'").at("unsafe"));
+                }
+            }
+        }
+        if self.matchers.is_empty() && self.extractors.is_empty() {
+            problems.push(Problem::warning("request has neither matchers nor extractors"));
+        }
+        problems.extend(lint_condition(&self.matchers_condition, "matchers-condition"));
+        for matcher in &self.matchers {
+            matcher.lint(problems);
+        }
+        for extractor in &self.extractors {
+            extractor.lint(problems);
+        }
+    }
+}
+
+impl NetworkRequest {
+    fn lint(&self, problems: &mut Vec<Problem>) {
+        if self.inputs.is_empty() {
+            problems.push(Problem::error("network request without inputs").at("inputs:"));
+        }
+        for input in &self.inputs {
+            match input.input_type.as_str() {
+                "text" => {}
+                "hex" if decode_hex(&input.data).is_none() && !input.data.contains("{{") => {
+                    problems.push(Problem::error(format!("invalid hex input '{}'", input.data)).at(&input.data));
+                }
+                "hex" => {}
+                other => problems.push(Problem::error(format!("unknown input type '{}'", other)).at(other)),
+            }
+        }
+        if self.matchers.is_empty() {
+            problems.push(Problem::error("network request without matchers"));
+        }
+        problems.extend(lint_condition(&self.matchers_condition, "matchers-condition"));
+        for matcher in &self.matchers {
+            if matcher.matcher_type == "status" || matcher.part == "header" {
+                problems.push(Problem::error("network responses have no status or headers").at(&matcher.matcher_type));
+                continue;
+            }
+            matcher.lint(problems);
+        }
+    }
+}
+
+impl Poc {
+    // 校验单条 POC，error 级问题会导致加载时跳过
+    fn lint(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if self.id.trim().is_empty() {
+            problems.push(Problem::error("empty id"));
+        }
+        if self.name.trim().is_empty() {
+            problems.push(Problem::error("empty name"));
+        }
+        match (self.requests.is_empty(), self.network.is_empty()) {
+            (true, true) => problems.push(Problem::error("no requests")),
+            (false, false) => problems.push(Problem::error("requests and network cannot be used together").at("network:")),
+            (false, true) if self.requests.iter().all(|r| r.matchers.is_empty()) => problems.push(Problem::error("no request has matchers")),
+            _ => {}
+        }
+        if let Some(severity) = &self.severity {
+            if !SEVERITIES.contains(&severity.to_lowercase().as_str()) {
+                problems.push(Problem::warning(format!("unknown severity '{}'", severity)).at(severity));
+            }
+        }
+        for request in &self.requests {
+            request.lint(&mut problems);
+        }
+        if self.samples.is_empty() {
+            problems.push(Problem::warning("no samples, edits to this POC are not covered by the sample tests"));
+        }
+        let steps = self.requests.len();
+        for request in self.requests.iter().take(steps.saturating_sub(1)) {
+            if !request.matchers.is_empty() && !request.extractors.is_empty() {
+                problems.push(Problem::warning("matchers on a non-final step end the chain early, move them to the last request"));
+            }
+        }
+        for request in &self.network {
+            request.lint(&mut problems);
+        }
+        problems
+    }
+}
+
+/// 校验 POC 文件内容，返回有效条目数与问题列表
+pub(crate) fn validate_pocs(content: &str) -> (usize, Vec<Issue>) {
+    let (pocs, issues) = Pocs::load(content);
+    (pocs.pocs.len(), issues)
+}
+
+// 端口扫描与 Web 扫描共用一份加载、筛选后的 POC
+static LOADED_POCS: OnceCell<Pocs> = OnceCell::new();
+
+fn loaded_pocs(otherset: &OtherSets) -> Result<&'static Pocs, Box<dyn Error + Send + Sync>> {
+    LOADED_POCS.get_or_try_init(|| {
+        let yaml_content = resource::load(Resource::Pocs)?;
+        let (mut pocs, issues) = Pocs::load(&yaml_content);
+        validate::report_skipped(&resource::source(Resource::Pocs), &issues);
+        for path in resource::nuclei_templates() {
+            match pocs.import_nuclei(path) {
+                Ok(count) => Print::infoprint(format!("Imported {} nuclei templates from {}", count, path.display()).as_str()),
+                Err(e) => Print::errprint(format!("Failed to import nuclei templates from {}: {}", path.display(), e).as_str()),
+            }
+        }
+        if !otherset.poc_severity.is_empty() || !otherset.poc_tags.is_empty() {
+            let remaining = pocs.filter(&otherset.poc_severity, &otherset.poc_tags);
+            Print::infoprint(format!("Filtered pocs by severity {:?} and tags {:?}: {} remaining", otherset.poc_severity, otherset.poc_tags, remaining).as_str());
+        }
+        Ok(pocs)
+    })
+}
+
+fn save_vulns(filename: &str, vuln_results: &Mutex<Vec<VulnInfo>>) {
+    let results = vuln_results.lock().unwrap();
+    if !results.is_empty() {
+        // 批量写入文件
+        let mut success = true;
+        for vuln in results.iter() {
+            if let Err(_e) = yaml_vuln_save_to_file(filename, &vuln.severity, &vuln.name, &vuln.url, &vuln.details) {
+                // eprintln!("保存漏洞信息失败: {}", e);
+                success = false;
+            }
+        }
+
+        if success {
+            Print::bannerprint(format!("{} vulnerability information has been saved to the file {}", results.len(), filename).as_str());
         }
     }
 }
 
-pub async fn pocsmain(targets: Vec<String>, client: Client, filename: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let yaml_content = include_str!("../config/pocs.yaml");
-    let pocs = Pocs::from_yaml(yaml_content)?;
+/// targets 对应的指纹命中结果为 fingerprints（url -> 产品名），otherset.run_all_pocs 为 true 时忽略指纹全部执行
+pub async fn pocsmain(targets: Vec<String>, client: Client, filename: &str, fingerprints: &HashMap<String, Vec<String>>, otherset: &OtherSets) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let run_all = otherset.run_all_pocs;
+    let pocs = loaded_pocs(otherset)?;
 
     let semaphore = Arc::new(Semaphore::new(200));
     let mut tasks = vec![];
@@ -226,8 +1344,14 @@ pub async fn pocsmain(targets: Vec<String>, client: Client, filename: &str) -> R
     // 创建一个共享的漏洞结果列表
     let vuln_results: Arc<Mutex<Vec<VulnInfo>>> = Arc::new(Mutex::new(Vec::new()));
 
+    let mut skipped = 0;
     for target in targets {
-        for poc in &pocs.pocs {
+        let products = fingerprints.get(target.trim_end_matches('/')).cloned().unwrap_or_default();
+        for poc in pocs.pocs.iter().filter(|p| !p.requests.is_empty()) {
+            if !run_all && !poc.applies_to(&products) {
+                skipped += 1;
+                continue;
+            }
             let client = client.clone();
             let target = target.clone();
             let poc = poc.clone();
@@ -241,25 +1365,669 @@ pub async fn pocsmain(targets: Vec<String>, client: Client, filename: &str) -> R
         }
     }
 
+    if !run_all {
+        Print::infoprint(format!("Selected {} poc checks by fingerprint, skipped {}", tasks.len(), skipped).as_str());
+    }
+
     // 等待所有任务完成
     futures::future::join_all(tasks).await;
 
     // 所有检测完成后，统一将结果写入文件
-    let results = vuln_results.lock().unwrap();
-    if !results.is_empty() {
-        // 批量写入文件
-        let mut success = true;
-        for vuln in results.iter() {
-            if let Err(_e) = yaml_vuln_save_to_file(filename, &vuln.name, &vuln.url) {
-                // eprintln!("保存漏洞信息失败: {}", e);
-                success = false;
+    save_vulns(filename, &vuln_results);
+
+    Ok(())
+}
+
+async fn check_network_poc(address: String, port: u16, poc: &Poc, vuln_results: Arc<Mutex<Vec<VulnInfo>>>) {
+    let session = PocSession::new(&format!("tcp://{}", address));
+    for request in poc.network.iter().filter(|r| r.applies_to_port(port)) {
+        let Ok(response) = send_network(&address, request, &session).await else {
+            continue;
+        };
+        if matchers_hit(&request.matchers, &request.matchers_condition, &response) {
+            let vuln = VulnInfo {
+                severity: poc.severity(),
+                name: poc.name.clone(),
+                url: address.clone(),
+                details: poc.details(),
+            };
+            Print::vulnportprint(format!("[{}][{}] {}", vuln.severity.to_uppercase(), vuln.name, vuln.url).as_str());
+            vuln_results.lock().unwrap().push(vuln);
+            return;
+        }
+    }
+}
+
+/// 对端口扫描得到的开放端口（ip -> 端口列表）执行 network POC
+pub async fn networkmain(open_ports: &[(String, Vec<u16>)], filename: &str, otherset: &OtherSets) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let pocs = loaded_pocs(otherset)?;
+    let network_pocs: Vec<Arc<Poc>> = pocs.pocs.iter().filter(|p| !p.network.is_empty()).cloned().map(Arc::new).collect();
+    if network_pocs.is_empty() {
+        return Ok(());
+    }
+
+    // 多个来源可能返回同一端口，去重后再探测
+    let mut addresses: Vec<(String, u16)> = Vec::new();
+    for (ip, ports) in open_ports {
+        let Ok(ip) = ip.parse::<std::net::IpAddr>() else {
+            continue;
+        };
+        for &port in ports {
+            let address = std::net::SocketAddr::new(ip, port).to_string();
+            if !addresses.iter().any(|(a, _)| *a == address) {
+                addresses.push((address, port));
             }
         }
+    }
 
-        if success {
-            Print::bannerprint(format!("{} vulnerability information has been saved to the file {}", results.len(), filename).as_str());
+    let semaphore = Arc::new(Semaphore::new(200));
+    let vuln_results: Arc<Mutex<Vec<VulnInfo>>> = Arc::new(Mutex::new(Vec::new()));
+    let mut tasks = vec![];
+    for (address, port) in addresses {
+        for poc in network_pocs.iter().filter(|p| p.network.iter().any(|r| r.applies_to_port(port))) {
+            let address = address.clone();
+            let poc = poc.clone();
+            let semaphore = semaphore.clone();
+            let vuln_results = vuln_results.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.expect("Semaphore acquire failed");
+                check_network_poc(address, port, &poc, vuln_results).await;
+            }));
         }
     }
+    Print::infoprint(format!("Running {} network poc checks", tasks.len()).as_str());
+    futures::future::join_all(tasks).await;
 
+    save_vulns(filename, &vuln_results);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&'static str, &'static str)], body: &str) -> ResponseData {
+        let mut map = HeaderMap::new();
+        for (k, v) in headers {
+            map.insert(*k, HeaderValue::from_static(v));
+        }
+        ResponseData { url: String::new(), status, headers: map, body: body.to_string(), raw: body.as_bytes().to_vec() }
+    }
+
+    fn request(yaml: &str) -> Request {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn regex_and_status_matchers_on_error_page() {
+        let req = request(r#"
+method: GET
+path: "/?id=1'"
+matchers-condition: and
+matchers:
+  - type: status
+    status: [500]
+  - type: regex
+    regex: ["SQL syntax.*MySQL"]
+"#);
+        assert!(req.matches(&response(500, &[], "You have an error in your SQL syntax; check the MySQL manual")));
+        assert!(!req.matches(&response(200, &[], "You have an error in your SQL syntax; check the MySQL manual")));
+    }
+
+    #[test]
+    fn negative_matcher_and_header_part() {
+        let req = request(r#"
+method: GET
+path: /admin
+redirects: false
+matchers-condition: and
+matchers:
+  - type: status
+    status: [302]
+  - type: word
+    part: header
+    words: ["Location: /index"]
+  - type: word
+    words: ["login"]
+    negative: true
+"#);
+        assert!(!req.redirects);
+        assert!(req.matches(&response(302, &[("location", "/index")], "")));
+        assert!(!req.matches(&response(302, &[("location", "/index")], "please login")));
+        assert!(!req.matches(&response(302, &[("location", "/login")], "")));
+    }
+
+    #[test]
+    fn size_binary_and_unknown_matchers() {
+        let zip = response(200, &[], "PK\u{3}\u{4}data");
+        let req = request(r#"
+method: GET
+path: /backup.zip
+matchers:
+  - type: binary
+    binary: ["504b0304"]
+"#);
+        assert!(req.matches(&zip));
+        let size = request("{method: GET, path: /, matchers: [{type: size, size: [8]}]}");
+        assert!(size.matches(&zip));
+        let unknown = request("{method: GET, path: /, matchers: [{type: dsl, negative: true}]}");
+        assert!(!unknown.matches(&zip));
+    }
+
+    #[test]
+    fn extractors_feed_variables_and_cookies() {
+        let login = request(r#"
+method: POST
+path: /api/login
+extractors:
+  - type: json
+    name: token
+    json: [".data.token"]
+  - type: regex
+    name: csrf
+    regex: ['csrf=([a-f0-9]+)']
+  - type: header
+    name: trace
+    kval: [x-trace-id]
+  - type: cookie
+    name: sid
+    kval: [JSESSIONID]
+"#);
+        let body = r#"{"data":{"token":"abc123"},"next":"/home?csrf=beef"}"#;
+        let mut resp = response(200, &[("x-trace-id", "t-1")], body);
+        resp.headers.append("set-cookie", HeaderValue::from_static("JSESSIONID=s1; Path=/; HttpOnly"));
+        resp.headers.append("set-cookie", HeaderValue::from_static("lang=zh"));
+
+        let mut session = PocSession::default();
+        assert!(session.extract(&login, &resp));
+        assert_eq!(session.render("/api/users?token={{token}}&c={{ csrf }}&t={{trace}}"), "/api/users?token=abc123&c=beef&t=t-1");
+        assert_eq!(session.render("{{sid}} {{7*7}} {{unknown}}"), "s1 {{7*7}} {{unknown}}");
+        assert_eq!(session.cookie_header().as_deref(), Some("JSESSIONID=s1; lang=zh"));
+
+        let missing = request("{method: GET, path: /, extractors: [{type: json, name: x, json: [nope]}]}");
+        assert!(!session.extract(&missing, &resp));
+    }
+
+    #[test]
+    fn dsl_lite_expressions() {
+        let resp = response(200, &[("server", "Apache/2.4")], "<title>Admin</title>");
+        for (expr, expected) in [
+            ("status_code == 200 && contains(body, 'Admin')", true),
+            ("status_code != 200 || contains(tolower(all_headers), \"apache\")", true),
+            ("!contains(body, \"Admin\")", false),
+            ("len(body) >= 20 && regex('<title>[A-Z]', body)", true),
+            ("contains_all(body, \"title\", \"missing\")", false),
+            ("contains_any(header, \"nginx\", \"Apache\")", true),
+        ] {
+            assert_eq!(DslExpr::parse(expr).unwrap().eval(&resp), expected, "{}", expr);
+        }
+        assert!(DslExpr::parse("md5(body) == 'x'").is_none());
+        assert!(DslExpr::parse("contains(body_1, 'x')").is_none());
+    }
+
+    #[test]
+    fn nuclei_template_maps_to_poc() {
+        let template: serde_yaml::Value = serde_yaml::from_str(r#"
+id: demo-login-bypass
+info:
+  name: Demo Login Bypass
+  severity: high
+  tags: cve,demo,fileupload,Upload
+  description: |
+    Token endpoint leaks an admin token.
+  reference:
+    - https://example.com/advisory
+  classification:
+    cve-id: cve-2099-0001
+http:
+  - method: GET
+    path:
+      - "{{BaseURL}}/api/token"
+    extractors:
+      - type: json
+        name: token
+        internal: true
+        json: [".token"]
+  - raw:
+      - |
+        POST /api/admin HTTP/1.1
+        Host: {{Hostname}}
+        Authorization: Bearer {{token}}
+        Content-Type: application/json
+
+        {"op":"list"}
+    matchers-condition: and
+    matchers:
+      - type: status
+        status: [200]
+      - type: dsl
+        dsl:
+          - "contains(body, 'users')"
+"#).unwrap();
+        let poc: Poc = serde_yaml::from_value(nuclei::convert(&template).unwrap()).unwrap();
+        assert_eq!(poc.name, "Demo Login Bypass");
+        // 产品标签被丢弃，别名归一为漏洞类型，导入的模板对任意目标执行
+        assert_eq!(poc.tags, vec!["cve", "upload"]);
+        assert!(poc.applies_to(&[]));
+        assert_eq!(poc.severity.as_deref(), Some("high"));
+        assert_eq!(poc.details(), "ID -> CVE-2099-0001 | Description -> Token endpoint leaks an admin token. | References -> https://example.com/advisory");
+        assert_eq!(poc.requests.len(), 2);
+        assert_eq!(poc.requests[0].path, "/api/token");
+        let admin = &poc.requests[1];
+        assert!(!admin.redirects && !admin.unsafe_raw);
+        let mut session = PocSession::new("http://127.0.0.1:8080");
+        session.vars.insert("token".to_string(), "t0k".to_string());
+        let raw = RawRequest::parse(&session.render(admin.raw.as_deref().unwrap())).unwrap();
+        assert_eq!((raw.method.as_str(), raw.path.as_str()), ("POST", "/api/admin"));
+        assert_eq!(raw.body, "{\"op\":\"list\"}");
+        assert!(!raw.headers.iter().any(|(k, _)| k == "Host"));
+        assert!(raw.headers.contains(&("Authorization".to_string(), "Bearer t0k".to_string())));
+        assert!(admin.matches(&response(200, &[], "users: 3")));
+        assert!(!admin.matches(&response(403, &[], "users: 3")));
+    }
+
+    #[test]
+    fn nuclei_regex_extractor_defaults_to_whole_match() {
+        let template: serde_yaml::Value = serde_yaml::from_str(r#"
+id: token-leak
+info: {name: token leak, severity: info}
+http:
+  - method: GET
+    path: ["{{BaseURL}}/"]
+    extractors:
+      - type: regex
+        name: whole
+        internal: true
+        regex: ["token=([a-z]+)"]
+      - type: regex
+        name: captured
+        group: 1
+        regex: ["token=([a-z]+)"]
+"#).unwrap();
+        let poc: Poc = serde_yaml::from_value(nuclei::convert(&template).unwrap()).unwrap();
+        let extractors = &poc.requests[0].extractors;
+        let page = response(200, &[], "token=abc;");
+        assert_eq!(extractors[0].extract(&page).as_deref(), Some("token=abc"));
+        assert_eq!(extractors[1].extract(&page).as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn nuclei_unsupported_features_are_reported() {
+        let template: serde_yaml::Value = serde_yaml::from_str(r#"
+id: demo-fuzz
+info:
+  name: Demo
+  severity: info
+variables:
+  a: b
+http:
+  - method: PUT
+    path:
+      - "{{BaseURL}}/{{randstr}}"
+    payloads:
+      p: [1]
+    matchers:
+      - type: xpath
+        xpath: ["/html"]
+"#).unwrap();
+        let reasons = nuclei::convert(&template).unwrap_err();
+        for expected in ["template field 'variables'", "request field 'payloads'", "placeholder {{randstr}}", "matcher type 'xpath'"] {
+            assert!(reasons.iter().any(|r| r == expected), "{} not in {:?}", expected, reasons);
+        }
+    }
+
+    #[test]
+    fn raw_response_parsing_handles_chunked_bodies() {
+        let data = b"HTTP/1.1 500 Internal Server Error\r\nTransfer-Encoding: chunked\r\nSet-Cookie: a=1\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let resp = parse_raw_response("http://x/".to_string(), data).unwrap();
+        assert_eq!(resp.status, 500);
+        assert_eq!(resp.body, "hello world");
+        assert_eq!(resp.set_cookies(), vec![("a".to_string(), "1".to_string())]);
+
+        let data = b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\nokEXTRA";
+        assert_eq!(parse_raw_response(String::new(), data).unwrap().body, "ok");
+        assert!(parse_raw_response(String::new(), b"garbage").is_none());
+    }
+
+    #[tokio::test]
+    async fn unsafe_raw_request_is_sent_verbatim() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nroot").await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        let req = request(r#"
+unsafe: true
+raw: |
+  PURGE /static/..;/admin/%2e%2e/ HTTP/1.1
+  Host: {{Hostname}}
+  X-Test: 1
+matchers:
+  - type: word
+    words: [root]
+"#);
+        let mut session = PocSession::new(&base);
+        session.cookies.push(("sid".to_string(), "s1".to_string()));
+        let resp = send_request(&Client::new(), &base, &req, &session).await.unwrap();
+        assert!(req.matches(&resp));
+        assert_eq!(resp.url, format!("{}/static/..;/admin/%2e%2e/", base));
+        let sent = server.await.unwrap();
+        assert!(sent.starts_with("PURGE /static/..;/admin/%2e%2e/ HTTP/1.1\r\n"));
+        assert!(sent.contains(&format!("Host: {}\r\n", base.trim_start_matches("http://"))));
+        assert!(sent.contains("Cookie: sid=s1\r\nConnection: close\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn network_request_sends_inputs_and_matches_banner() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 7];
+            socket.read_exact(&mut buf).await.unwrap();
+            socket.write_all(b"\x4eJRMI-ack").await.unwrap();
+            buf
+        });
+        let poc: Poc = serde_yaml::from_str(r#"
+id: rmi
+name: rmi
+network:
+  - inputs:
+      - data: 'JRMI\x00\x02'
+      - data: "4b"
+        type: hex
+    read-size: 16
+    matchers:
+      - type: binary
+        binary: ["4e4a524d49"]
+      - type: word
+        words: ["ack"]
+    matchers-condition: and
+samples:
+  - vulnerable: true
+    responses:
+      - body: "NJRMI ack"
+"#).unwrap();
+        assert!(poc.lint().is_empty());
+        let request = &poc.network[0];
+        let session = PocSession::new(&format!("tcp://{}", address));
+        let resp = send_network(&address, request, &session).await.unwrap();
+        assert!(matchers_hit(&request.matchers, &request.matchers_condition, &resp));
+        assert_eq!(server.await.unwrap(), b"JRMI\x00\x02\x4b");
+
+        assert!(request.applies_to_port(8080));
+        assert!(!request.applies_to_port(3306));
+        assert_eq!(unescape(r"a\r\n\x0G\\b\q"), b"a\r\n\\x0G\\b\\q".to_vec());
+    }
+
+    #[test]
+    fn severity_and_tag_filters() {
+        let (pocs, _) = Pocs::load(include_str!("../config/pocs.yaml"));
+        assert!(pocs.pocs.iter().all(|p| p.severity.is_some()));
+
+        let poc = |yaml: &str| -> Poc { serde_yaml::from_str(yaml).unwrap() };
+        let generic = poc("{id: a, name: a, severity: low, tags: [file-read, cve2021], requests: []}");
+        assert!(generic.applies_to(&[]));
+        let oa = poc("{id: b, name: b, severity: HIGH, tags: [泛微, oa, file-read], requests: []}");
+        assert!(!oa.applies_to(&["nginx".to_string()]));
+        assert!(oa.applies_to(&["泛微-E-Office".to_string()]));
+
+        let list = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(oa.selected(&list(&["high", "critical"]), &[]));
+        assert!(!generic.selected(&list(&["high", "critical"]), &[]));
+        assert!(oa.selected(&[], &list(&["OA"])));
+        assert!(!oa.selected(&list(&["high"]), &list(&["sqli"])));
+        assert!(!poc("{id: c, name: c, requests: []}").selected(&list(&["info"]), &[]));
+    }
+
+    #[test]
+    fn embedded_pocs_validate() {
+        let (count, issues) = validate_pocs(include_str!("../config/pocs.yaml"));
+        assert!(count > 0);
+        let errors: Vec<String> = issues.iter().filter(|i| i.level == validate::Level::Error).map(|i| i.to_string()).collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn invalid_pocs_are_skipped_with_line_numbers() {
+        let yaml = r#"# test
+pocs:
+  - id: ok
+    name: ok
+    requests:
+      - path: /
+        matchers:
+          - type: word
+            words: ["ok"]
+  - id: bad-regex
+    name: bad regex
+    requests:
+      - path: /
+        matchers:
+          - type: regex
+            regex: ["(unclosed"]
+  - id: ok
+    name: duplicate
+    requests:
+      - path: /
+        matchers:
+          - type: status
+            status: [200]
+  - id: bad-type
+    name: bad type
+    requests:
+      - path: /
+        matchers-condition: xor
+        matchers:
+          - type: magic
+            words: []
+  - id: schema
+    name: schema
+    requests: "nope"
+  - id: empty-words
+    name: empty words
+    severity: urgent
+    requests:
+      - path: /
+        matchers:
+          - type: word
+            words: []
+"#;
+        let (pocs, issues) = Pocs::load(yaml);
+        assert_eq!(pocs.pocs.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["ok"]);
+        let found = |line: usize, text: &str| issues.iter().any(|i| i.line == line && i.message.contains(text));
+        assert!(found(16, "invalid regex"), "{:?}", issues);
+        assert!(found(17, "duplicate id, first defined at line 3"), "{:?}", issues);
+        assert!(found(28, "invalid matchers-condition"), "{:?}", issues);
+        assert!(found(30, "unknown matcher type 'magic'"), "{:?}", issues);
+        assert!(found(34, "schema error: requests: invalid type"), "{:?}", issues);
+        assert!(found(37, "unknown severity"), "{:?}", issues);
+        assert!(found(42, "empty words list"), "{:?}", issues);
+        assert!(found(3, "no samples"), "{:?}", issues);
+    }
+
+    #[test]
+    fn matchers_on_chain_steps_are_warned() {
+        let chain = |first: &str| -> Poc {
+            serde_yaml::from_str(&format!(
+                "{{id: c, name: c, requests: [{{path: /login, {}extractors: [{{type: cookie, name: sid, kval: [sid]}}]}}, {{path: /admin, matchers: [{{type: status, status: [200]}}]}}]}}",
+                first
+            ))
+            .unwrap()
+        };
+        let warned = |poc: &Poc| poc.lint().iter().any(|p| p.level == validate::Level::Warning && p.message.contains("non-final step"));
+        assert!(warned(&chain("matchers: [{type: status, status: [302]}], ")));
+        assert!(!warned(&chain("")));
+    }
+
+    #[test]
+    fn whole_file_syntax_errors_are_reported() {
+        let yaml = r#"pocs:
+  - id: a
+    name: a
+    requests:
+      - path: /
+        matchers:
+          - type: status
+            status: [200]
+extra: [unclosed
+"#;
+        let (pocs, issues) = Pocs::load(yaml);
+        assert_eq!(pocs.pocs.len(), 1);
+        assert!(issues.iter().any(|i| i.level == validate::Level::Error && i.line >= 9 && i.message.starts_with("syntax error")), "{:?}", issues);
+    }
+
+
+    // pocs.yaml 中 POC 附带的样例：vulnerable 为 true 时应恰好报告一次，否则不应报告
+    #[derive(Deserialize)]
+    struct Sample {
+        vulnerable: bool,
+        #[serde(default)]
+        responses: Vec<SampleResponse>,
+    }
+
+    fn default_sample_status() -> u16 {
+        200
+    }
+
+    #[derive(Deserialize, Clone)]
+    struct SampleResponse {
+        // 只响应该路径的请求，未设置时按顺序响应
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default = "default_sample_status")]
+        status: u16,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        // network POC 的响应内容支持 \x00 等转义
+        #[serde(default)]
+        body: String,
+    }
+
+    #[derive(Deserialize)]
+    struct PocSamples {
+        id: String,
+        #[serde(default)]
+        samples: Vec<Sample>,
+    }
+
+    #[derive(Deserialize)]
+    struct SampleFile {
+        pocs: Vec<PocSamples>,
+    }
+
+    // 读取一个完整的 HTTP 请求，返回请求路径
+    async fn read_http_request(socket: &mut TcpStream) -> Option<String> {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let head_end = loop {
+            if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos;
+            }
+            let n = socket.read(&mut buf).await.ok().filter(|n| *n > 0)?;
+            data.extend_from_slice(&buf[..n]);
+        };
+        let head = String::from_utf8_lossy(&data[..head_end]).into_owned();
+        let length = head
+            .lines()
+            .filter_map(|l| l.split_once(':'))
+            .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        while data.len() < head_end + 4 + length {
+            match socket.read(&mut buf).await {
+                Ok(n) if n > 0 => data.extend_from_slice(&buf[..n]),
+                _ => break,
+            }
+        }
+        head.lines().next()?.split_whitespace().nth(1).map(String::from)
+    }
+
+    // 本地替身服务：每个连接取一条未使用的样例响应（优先匹配路径），没有可用响应时返回 404 或直接关闭
+    async fn stand_in(responses: Vec<SampleResponse>, network: bool) -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let mut unused = responses;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                if network {
+                    let mut buf = [0u8; 1024];
+                    let _ = timeout(Duration::from_millis(200), socket.read(&mut buf)).await;
+                    if !unused.is_empty() {
+                        let _ = socket.write_all(&unescape(&unused.remove(0).body)).await;
+                    }
+                    continue;
+                }
+                let Some(path) = read_http_request(&mut socket).await else {
+                    continue;
+                };
+                let response = unused
+                    .iter()
+                    .position(|r| r.path.as_deref().map(|p| p == path).unwrap_or(true))
+                    .map(|i| unused.remove(i))
+                    .unwrap_or(SampleResponse { path: None, status: 404, headers: HashMap::new(), body: String::new() });
+                let mut head = format!("HTTP/1.1 {} Sample\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, response.body.len());
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(response.body.as_bytes()).await;
+            }
+        });
+        (address, handle)
+    }
+
+    #[tokio::test]
+    async fn pocs_match_their_recorded_samples() {
+        let yaml = include_str!("../config/pocs.yaml");
+        let (pocs, _) = Pocs::load(yaml);
+        let file: SampleFile = serde_yaml::from_str(yaml).expect("samples should parse");
+        // 样例响应即最终响应，不跟随跳转
+        let client = Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+        let mut checked = 0;
+        let mut failures = Vec::new();
+        for entry in file.pocs.iter().filter(|p| !p.samples.is_empty()) {
+            let poc = pocs.pocs.iter().find(|p| p.id == entry.id).unwrap_or_else(|| panic!("poc {} failed to load", entry.id));
+            for (index, sample) in entry.samples.iter().enumerate() {
+                let network = !poc.network.is_empty();
+                let (address, server) = stand_in(sample.responses.clone(), network).await;
+                let results = Arc::new(Mutex::new(Vec::new()));
+                if network {
+                    // 替身端口是随机的，忽略 POC 限定的端口
+                    let mut poc = poc.clone();
+                    poc.network.iter_mut().for_each(|r| r.ports.clear());
+                    check_network_poc(address.to_string(), address.port(), &poc, results.clone()).await;
+                } else {
+                    check_poc(client.clone(), format!("http://{}", address), poc.clone(), results.clone()).await;
+                }
+                server.abort();
+                let found = results.lock().unwrap().len();
+                let expected = usize::from(sample.vulnerable);
+                if found != expected {
+                    failures.push(format!("{} sample {}: expected {} findings, got {}", entry.id, index + 1, expected, found));
+                }
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+        assert!(failures.is_empty(), "{:#?}", failures);
+    }
+
+    #[tokio::test]
+    async fn poc_is_reported_once_across_paths() {
+        let poc: Poc = serde_yaml::from_str("{id: a, name: a, requests: [{path: /a, matchers: [{type: word, words: [leak]}]}, {path: /b, matchers: [{type: word, words: [leak]}]}]}").unwrap();
+        let sample = |path: &str| SampleResponse { path: Some(path.to_string()), status: 200, headers: HashMap::new(), body: "leak".to_string() };
+        let (address, server) = stand_in(vec![sample("/a"), sample("/b")], false).await;
+        let results = Arc::new(Mutex::new(Vec::new()));
+        check_poc(Client::new(), format!("http://{}", address), poc, results.clone()).await;
+        server.abort();
+        assert_eq!(results.lock().unwrap().len(), 1);
+    }
+
+}
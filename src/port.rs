@@ -6,8 +6,6 @@ use futures::future::join_all;
 use tokio::sync::{Mutex, Semaphore};
 use tokio::net::TcpStream;
 use tokio::time::{timeout, Duration};
-use tokio::task;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use async_trait::async_trait;
 use base64::engine::general_purpose::STANDARD;
 use base64::engine::Engine as _;
@@ -17,9 +15,9 @@ use reqwest::header::HeaderMap;
 use std::collections::HashSet;
 use std::hash::RandomState;
 use crate::infoscan::OtherSets;
-use crate::tofile::other_save_to_file;
 use crate::tofile::realip_to_file;
 use crate::outprint::Print;
+use crate::pocscan::networkmain;
 use serde_json::json;
 
 
@@ -563,107 +561,6 @@ fn is_ip_in_cidr(ip: &std::net::IpAddr, cidr: &str) -> bool {
     false
 }
 
-async fn identify_service(ip: IpAddr, port: u16, timeout_duration: Duration, filename: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let skip_ports = [22, 21, 153, 445, 3306, 1521, 5432, 1433, 6379, 27017, 9200, 53, 23];
-    if skip_ports.contains(&port) {
-        return Ok(());
-    }
-
-    let socket_addr = SocketAddr::new(ip, port);
-
-    if let Ok(is_jdwp) = check_jdwp(socket_addr, timeout_duration).await {
-        if is_jdwp {
-            Print::vulnportprint(format!("IP {}:{} Jdwp service detected", ip, port).as_str());
-            let _ = other_save_to_file(filename, format!("IP {}:{} Jdwp service detected", ip, port).as_str());
-            return Ok(());
-        }
-    }
-
-    if let Ok(is_activemq) = check_activemq(socket_addr, timeout_duration).await {
-        if is_activemq {
-            Print::vulnportprint(format!("IP {}:{} ActiveMQ service detected", ip, port).as_str());
-            let _ = other_save_to_file(filename, format!("IP {}:{} ActiveMQ service detected", ip, port).as_str());
-            return Ok(());
-        }
-    }
-
-    if let Ok(is_rmi) = check_rmi(socket_addr, timeout_duration).await {
-        if is_rmi {
-            Print::vulnportprint(format!("IP {}:{} RMI service detected", ip, port).as_str());
-            let _ = other_save_to_file(filename, format!("IP {}:{} RMI service detected", ip, port).as_str());
-            return Ok(());
-        }
-    }
-
-    Ok(())
-}
-
-async fn check_jdwp(socket_addr: SocketAddr, timeout_duration: Duration) -> Result<bool, Box<dyn Error + Send + Sync>> {
-    let stream = timeout(timeout_duration, TcpStream::connect(socket_addr)).await??;
-    let mut stream = stream;
-    stream.write_all(b"JDWP-Handshake").await?;
-    stream.flush().await?;
-
-    let mut buffer = [0; 14];
-    let result = timeout(timeout_duration, stream.read_exact(&mut buffer)).await;
-
-    match result {
-        Ok(Ok(_)) => {
-            if buffer == b"JDWP-Handshake"[..] {
-                return Ok(true);
-            }
-        }
-        _ => {}
-    }
-    Ok(false)
-}
-
-async fn check_activemq(socket_addr: SocketAddr, timeout_duration: Duration) -> Result<bool, Box<dyn Error + Send + Sync>> {
-    let stream = timeout(timeout_duration, TcpStream::connect(socket_addr)).await??;
-    let mut stream = stream;
-
-    let openwire_frame = b"\x00\x00\x00\x0f\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
-    stream.write_all(openwire_frame).await?;
-    stream.flush().await?;
-
-    let mut buffer = vec![0; 1024];
-    let result = timeout(timeout_duration, stream.read(&mut buffer)).await;
-
-    match result {
-        Ok(Ok(n)) if n > 0 => {
-            let response = String::from_utf8_lossy(&buffer[..n]);
-            if response.contains("ActiveMQ") {
-                return Ok(true);
-            }
-        }
-        _ => {}
-    }
-    Ok(false)
-}
-
-async fn check_rmi(socket_addr: SocketAddr, timeout_duration: Duration) -> Result<bool, Box<dyn Error + Send + Sync>> {
-    let stream = timeout(timeout_duration, TcpStream::connect(socket_addr)).await??;
-    let mut stream = stream;
-
-    let jrmp_request = b"JRMI\x00\x02\x4b";
-    stream.write_all(jrmp_request).await?;
-    stream.flush().await?;
-
-    let mut buffer = vec![0; 256];
-    let result = timeout(timeout_duration, stream.read(&mut buffer)).await;
-
-    match result {
-        Ok(Ok(n)) if n > 0 => {
-            let response = String::from_utf8_lossy(&buffer[..n]);
-            if response.contains("JRMI") || response.contains("rmi") {
-                return Ok(true);
-            }
-        }
-        _ => {}
-    }
-    Ok(false)
-}
-
 pub async fn portmain(
     ips: &Vec<String>,
     filename: &str,
@@ -984,26 +881,12 @@ pub async fn portmain(
     let unique_links = final_links.res_links();
 
     if !unique_ports.is_empty() {
-        Print::infoprint("Starting banner identification for all open ports");
-        let mut banner_tasks = vec![];
+        Print::infoprint("Starting network pocs for all open ports");
         let open_ports_map = open_ports_map.lock().await;
-
-        for (ip, ports) in open_ports_map.iter() {
-            let ip_addr = match IpAddr::from_str(ip) {
-                Ok(ip) => ip,
-                Err(_) => continue,
-            };
-            for &port in ports {
-                let filename_clone = filename.to_string();
-                let task = task::spawn(async move {
-                    let _ = identify_service(ip_addr, port, Duration::from_secs(3), &filename_clone).await;
-                });
-                banner_tasks.push(task);
-            }
+        if let Err(e) = networkmain(&open_ports_map, filename, otherset).await {
+            Print::errprint(format!("Network pocs failed: {}", e).as_str());
         }
-
-        join_all(banner_tasks).await;
-        Print::infoprint("Port banner identification completed");
+        Print::infoprint("Network pocs completed");
     }
 
     let _ = realip_to_file(filename, &*filtered_non_cdn_ips_after_fofa);
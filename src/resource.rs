@@ -0,0 +1,256 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use once_cell::sync::OnceCell;
+use crate::outprint::Print;
+
+// 全局资源加载器，在 main 中根据命令行参数初始化
+static LOADER: OnceCell<ResourceLoader> = OnceCell::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Fingers,
+    Pocs,
+    Payloads,
+    Config,
+    Paths,
+    Subdict,
+}
+
+impl Resource {
+    fn file_name(&self) -> &'static str {
+        match self {
+            Resource::Fingers => "finger.json",
+            Resource::Pocs => "pocs.yaml",
+            Resource::Payloads => "payloads.yaml",
+            Resource::Config => "config.toml",
+            Resource::Paths => "path.txt",
+            Resource::Subdict => "subdomain.txt",
+        }
+    }
+
+    // 与仓库目录结构保持一致：配置在 config/，字典在 dict/
+    fn sub_dir(&self) -> &'static str {
+        match self {
+            Resource::Paths | Resource::Subdict => "dict",
+            _ => "config",
+        }
+    }
+
+    fn embedded(&self) -> &'static str {
+        match self {
+            Resource::Fingers => include_str!("../config/finger.json"),
+            Resource::Pocs => include_str!("../config/pocs.yaml"),
+            Resource::Payloads => include_str!("../config/payloads.yaml"),
+            Resource::Config => include_str!("../config/config.toml"),
+            Resource::Paths => include_str!("../dict/path.txt"),
+            Resource::Subdict => include_str!("../dict/subdomain.txt"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ResourceLoader {
+    search_dirs: Vec<PathBuf>,
+    overrides: HashMap<Resource, PathBuf>,
+    fingerprint_imports: Vec<PathBuf>,
+    nuclei_templates: Vec<PathBuf>,
+}
+
+impl ResourceLoader {
+    /// 查找顺序：--config-dir -> $REND_HOME -> $XDG_CONFIG_HOME/rend (~/.config/rend)
+    pub fn new(config_dir: Option<&str>) -> Self {
+        Self::with_env(config_dir, |key| env::var(key).ok())
+    }
+
+    // 环境变量通过 var 读取，测试中可替换，不修改进程环境
+    fn with_env(config_dir: Option<&str>, var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |key: &str| var(key).filter(|v| !v.is_empty());
+        let mut search_dirs = Vec::new();
+        if let Some(dir) = config_dir {
+            search_dirs.push(PathBuf::from(dir));
+        }
+        if let Some(home) = var("REND_HOME") {
+            search_dirs.push(PathBuf::from(home));
+        }
+        match var("XDG_CONFIG_HOME") {
+            Some(xdg) => search_dirs.push(PathBuf::from(xdg).join("rend")),
+            None => {
+                if let Some(home) = var("HOME") {
+                    search_dirs.push(PathBuf::from(home).join(".config").join("rend"));
+                }
+            }
+        }
+        ResourceLoader { search_dirs, overrides: HashMap::new(), fingerprint_imports: Vec::new(), nuclei_templates: Vec::new() }
+    }
+
+    /// 单独指定某个资源文件，优先级最高
+    pub fn with_override(mut self, resource: Resource, path: &str) -> Self {
+        self.overrides.insert(resource, PathBuf::from(path));
+        self
+    }
+
+    /// 额外导入的第三方指纹库（EHole / Wappalyzer / Goby）
+    pub fn with_fingerprint_import(mut self, path: &str) -> Self {
+        self.fingerprint_imports.push(PathBuf::from(path));
+        self
+    }
+
+    /// 额外导入的 nuclei 模板文件或目录
+    pub fn with_nuclei_templates(mut self, path: &str) -> Self {
+        self.nuclei_templates.push(PathBuf::from(path));
+        self
+    }
+
+    fn locate(&self, resource: Resource) -> Option<PathBuf> {
+        self.search_dirs.iter().find_map(|dir| {
+            [dir.join(resource.sub_dir()).join(resource.file_name()), dir.join(resource.file_name())]
+                .into_iter()
+                .find(|path| path.is_file())
+        })
+    }
+
+    pub fn load(&self, resource: Resource) -> io::Result<Cow<'static, str>> {
+        if let Some(path) = self.overrides.get(&resource) {
+            // 显式指定的文件读取失败时直接报错，不回退默认
+            return fs::read_to_string(path).map(Cow::Owned).map_err(|e| {
+                io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+            });
+        }
+        if let Some(path) = self.locate(resource) {
+            match fs::read_to_string(&path) {
+                Ok(content) => return Ok(Cow::Owned(content)),
+                Err(e) => Print::errprint(format!("Failed to read {}: {}, using embedded default", path.display(), e).as_str()),
+            }
+        }
+        Ok(Cow::Borrowed(resource.embedded()))
+    }
+
+    /// 返回资源的实际来源，用于启动时输出
+    pub fn source(&self, resource: Resource) -> String {
+        if let Some(path) = self.overrides.get(&resource) {
+            return path.display().to_string();
+        }
+        match self.locate(resource) {
+            Some(path) => path.display().to_string(),
+            None => "embedded".to_string(),
+        }
+    }
+}
+
+pub fn init(loader: ResourceLoader) {
+    let _ = LOADER.set(loader);
+}
+
+fn loader() -> &'static ResourceLoader {
+    LOADER.get_or_init(|| ResourceLoader::new(None))
+}
+
+pub fn load(resource: Resource) -> io::Result<Cow<'static, str>> {
+    loader().load(resource)
+}
+
+pub fn source(resource: Resource) -> String {
+    loader().source(resource)
+}
+
+pub fn fingerprint_imports() -> impl Iterator<Item = &'static Path> {
+    loader().fingerprint_imports.iter().map(|p| p.as_path())
+}
+
+pub fn nuclei_templates() -> impl Iterator<Item = &'static Path> {
+    loader().nuclei_templates.iter().map(|p| p.as_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个测试使用独立的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("rend-resource-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, path: &str, content: &str) -> String {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            path.display().to_string()
+        }
+
+        fn path(&self, sub: &str) -> String {
+            self.0.join(sub).display().to_string()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn loader(config_dir: Option<&str>, vars: &[(&str, String)]) -> ResourceLoader {
+        ResourceLoader::with_env(config_dir, |key| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.clone()))
+    }
+
+    #[test]
+    fn search_dirs_follow_priority() {
+        let vars = [("REND_HOME", "/rend".to_string()), ("XDG_CONFIG_HOME", "/xdg".to_string()), ("HOME", "/home/u".to_string())];
+        let dirs = loader(Some("/cli"), &vars).search_dirs;
+        assert_eq!(dirs, vec![PathBuf::from("/cli"), PathBuf::from("/rend"), PathBuf::from("/xdg/rend")]);
+        // 未设置或为空的 XDG_CONFIG_HOME 回退到 ~/.config/rend
+        let vars = [("REND_HOME", String::new()), ("XDG_CONFIG_HOME", String::new()), ("HOME", "/home/u".to_string())];
+        assert_eq!(loader(None, &vars).search_dirs, vec![PathBuf::from("/home/u/.config/rend")]);
+    }
+
+    #[test]
+    fn earlier_dirs_and_sub_dirs_win() {
+        let tmp = TempDir::new("order");
+        tmp.write("cli/config/pocs.yaml", "cli");
+        tmp.write("home/pocs.yaml", "home-flat");
+        tmp.write("home/config/finger.json", "home-finger");
+        tmp.write("home/dict/path.txt", "home-paths");
+        tmp.write("home/path.txt", "home-paths-flat");
+        tmp.write("xdg/rend/config/payloads.yaml", "xdg");
+        let vars = [("REND_HOME", tmp.path("home")), ("XDG_CONFIG_HOME", tmp.path("xdg"))];
+        let loader = loader(Some(&tmp.path("cli")), &vars);
+        assert_eq!(loader.load(Resource::Pocs).unwrap(), "cli");
+        assert_eq!(loader.load(Resource::Fingers).unwrap(), "home-finger");
+        assert_eq!(loader.load(Resource::Paths).unwrap(), "home-paths");
+        assert_eq!(loader.load(Resource::Payloads).unwrap(), "xdg");
+        // 字典不在 config/ 下查找
+        tmp.write("cli/config/subdomain.txt", "misplaced");
+        assert!(matches!(loader.load(Resource::Subdict).unwrap(), Cow::Borrowed(_)));
+        assert_eq!(loader.source(Resource::Pocs), tmp.path("cli/config/pocs.yaml"));
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let tmp = TempDir::new("override");
+        tmp.write("cli/config/pocs.yaml", "cli");
+        let file = tmp.write("mine.yaml", "override");
+        let loader = loader(Some(&tmp.path("cli")), &[]).with_override(Resource::Pocs, &file);
+        assert_eq!(loader.load(Resource::Pocs).unwrap(), "override");
+        assert_eq!(loader.source(Resource::Pocs), file);
+        // 指定的文件不存在时报错而不是回退
+        let missing = ResourceLoader::with_env(None, |_| None).with_override(Resource::Pocs, &tmp.path("missing.yaml"));
+        assert!(missing.load(Resource::Pocs).is_err());
+    }
+
+    #[test]
+    fn missing_files_fall_back_to_embedded() {
+        let tmp = TempDir::new("embedded");
+        let loader = loader(Some(&tmp.path("empty")), &[("REND_HOME", tmp.path("none"))]);
+        for resource in [Resource::Fingers, Resource::Pocs, Resource::Payloads, Resource::Config, Resource::Paths, Resource::Subdict] {
+            assert_eq!(loader.load(resource).unwrap(), resource.embedded());
+            assert_eq!(loader.source(resource), "embedded");
+        }
+    }
+}
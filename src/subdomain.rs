@@ -4,14 +4,25 @@ use trust_dns_resolver::TokioAsyncResolver;
 use std::sync::{Arc};
 use tokio::sync::Semaphore;
 // use std::collections::HashSet;
+use trust_dns_resolver::proto::rr::RData;
 use crate::outprint;
+use crate::resource::{self, Resource};
 use std::net::IpAddr;
 
-fn read_wordlist() -> Vec<String> {
-    include_str!("../dict/subdomain.txt")
+// 字典爆破得到的子域名结果
+#[derive(Debug, Clone)]
+pub struct SubdomainResult {
+    pub hostname: String,
+    pub ips: Vec<String>,
+    pub cnames: Vec<String>,
+}
+
+fn read_wordlist() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(resource::load(Resource::Subdict)?
         .lines()
         .map(|line| line.trim().to_string())
-        .collect()
+        .filter(|line| !line.is_empty())
+        .collect())
 }
 
 // 检查是否是泛解析域名
@@ -23,8 +34,8 @@ async fn is_wildcard(domain: &str, resolver: Arc<TokioAsyncResolver>) -> bool {
     }
 }
 
-pub async fn scan_subdomains(domain: &str, threads: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let wordlist = read_wordlist();
+pub async fn scan_subdomains(domain: &str, threads: usize) -> Result<Vec<SubdomainResult>, Box<dyn std::error::Error>> {
+    let wordlist = read_wordlist()?;
     let resolver = TokioAsyncResolver::tokio(
         ResolverConfig::cloudflare(),
         ResolverOpts::default(),
@@ -67,15 +78,33 @@ pub async fn scan_subdomains(domain: &str, threads: usize) -> Result<(), Box<dyn
 
                 if !valid_ips.is_empty() {
                     outprint::Print::bannerprint(&format!("Found: {} -> {:?}", full_domain, valid_ips));
+                    // 记录解析链路中的 CNAME
+                    let cnames: Vec<String> = response
+                        .as_lookup()
+                        .record_iter()
+                        .filter_map(|record| match record.data() {
+                            Some(RData::CNAME(name)) => Some(name.to_utf8().trim_end_matches('.').to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    return Some(SubdomainResult {
+                        hostname: full_domain,
+                        ips: valid_ips.iter().map(|ip| ip.to_string()).collect(),
+                        cnames,
+                    });
                 }
             }
+            None
         });
         tasks.push(task);
     }
 
+    let mut results = Vec::new();
     for task in tasks {
-        task.await?;
+        if let Some(result) = task.await? {
+            results.push(result);
+        }
     }
 
-    Ok(())
+    Ok(results)
 }
\ No newline at end of file
@@ -33,6 +33,20 @@ pub fn vuln_save_to_file(file_name: &str, domains: &[String]) -> io::Result<()>
     Ok(())
 }
 
+pub fn bruteforce_save_to_file(file_name: &str, domains: &[String]) -> io::Result<()> {
+    // 打开文件（如果文件不存在则创建，存在则追加内容）
+    let mut file = OpenOptions::new()
+        .create(true) // 如果文件不存在则创建
+        .append(true) // 如果文件存在则追加内容
+        .open(file_name)?;
+    writeln!(file, "\n[Bruteforce]")?;
+    for domain in domains {
+        writeln!(file, "{}", domain)?;
+    }
+
+    Ok(())
+}
+
 pub fn realip_to_file(file_name: &str, domains: &[String]) -> io::Result<()> {
     // 打开文件（如果文件不存在则创建，存在则追加内容）
     let mut file = OpenOptions::new()
@@ -47,13 +61,17 @@ pub fn realip_to_file(file_name: &str, domains: &[String]) -> io::Result<()> {
     Ok(())
 }
 
-pub fn yaml_vuln_save_to_file(file_name: &str, name:&str,domains: &str) -> io::Result<()> {
+pub fn yaml_vuln_save_to_file(file_name: &str, severity: &str, name:&str, domains: &str, details: &str) -> io::Result<()> {
     // 打开文件（如果文件不存在则创建，存在则追加内容）
     let mut file = OpenOptions::new()
         .create(true) // 如果文件不存在则创建
         .append(true) // 如果文件存在则追加内容
         .open(file_name)?;
-    writeln!(file, "[*] | {} | {}", name,domains)?;
+    if details.is_empty() {
+        writeln!(file, "[*] | {} | {} | {}", severity.to_uppercase(), name, domains)?;
+    } else {
+        writeln!(file, "[*] | {} | {} | {} | {}", severity.to_uppercase(), name, domains, details)?;
+    }
 
     Ok(())
 }
@@ -120,6 +138,28 @@ pub fn req_urls_save_to_file(file_name: &str,domain: &str, status: &u64, lens: &
     Ok(())
 }
 
+pub fn favicon_save_to_file(file_name: &str, url: &str, hash: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true) // 如果文件不存在则创建
+        .append(true) // 如果文件存在则追加内容
+        .open(file_name)?;
+    writeln!(file, "[FaviconHash] {} | {}", url, hash)?;
+    Ok(())
+}
+
+pub fn finger_save_to_file(file_name: &str, url: &str, product: &str, details: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true) // 如果文件不存在则创建
+        .append(true) // 如果文件存在则追加内容
+        .open(file_name)?;
+    if details.is_empty() {
+        writeln!(file, "[CMS] {} | {}", url, product)?;
+    } else {
+        writeln!(file, "[CMS] {} | {} | {}", url, product, details)?;
+    }
+    Ok(())
+}
+
 pub fn other_save_to_file(file_name: &str,other:&str) -> io::Result<()> {
     let mut file = OpenOptions::new()
         .create(true) // 如果文件不存在则创建
